@@ -0,0 +1,8 @@
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS session_id SERIAL;
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS user_agent TEXT;
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS ip_address TEXT;
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS last_used_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE UNIQUE INDEX IF NOT EXISTS sessions_session_id_idx ON sessions (session_id);
+CREATE INDEX IF NOT EXISTS sessions_user_id_idx ON sessions (user_id);
@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS assignments (
+    assignment_id SERIAL PRIMARY KEY,
+    class_id INTEGER NOT NULL REFERENCES classes (class_id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    target_mastery DOUBLE PRECISION NOT NULL CHECK (target_mastery > 0 AND target_mastery <= 1),
+    question_count INTEGER NOT NULL CHECK (question_count > 0),
+    due_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS assignments_class_id_idx ON assignments (class_id);
+
+CREATE TABLE IF NOT EXISTS assignment_skills (
+    assignment_id INTEGER NOT NULL REFERENCES assignments (assignment_id) ON DELETE CASCADE,
+    skill_id INTEGER NOT NULL REFERENCES skills (skill_id) ON DELETE CASCADE,
+    PRIMARY KEY (assignment_id, skill_id)
+);
+
+CREATE TABLE IF NOT EXISTS assignment_progress (
+    assignment_id INTEGER NOT NULL REFERENCES assignments (assignment_id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+    skill_id INTEGER NOT NULL REFERENCES skills (skill_id) ON DELETE CASCADE,
+    questions_answered INTEGER NOT NULL DEFAULT 0,
+    mastery DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (assignment_id, user_id, skill_id)
+);
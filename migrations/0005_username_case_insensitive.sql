@@ -0,0 +1,23 @@
+-- Usernames that differ only by case cannot both keep their name once lookups ignore case.
+-- Renaming either account is left to an administrator, so refuse to continue until they have.
+DO $$
+DECLARE
+    conflicts TEXT;
+BEGIN
+    SELECT string_agg(usernames, '; ')
+    INTO conflicts
+    FROM (
+        SELECT string_agg(username, ', ' ORDER BY user_id) AS usernames
+        FROM users
+        GROUP BY lower(username)
+        HAVING COUNT(*) > 1
+    ) duplicates;
+
+    IF conflicts IS NOT NULL THEN
+        RAISE EXCEPTION 'Usernames must be unique ignoring case, but these accounts clash: %', conflicts
+            USING HINT = 'Rename all but one account in each group (UPDATE users SET username = ... WHERE user_id = ...), then restart the service.';
+    END IF;
+END
+$$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_lower_idx ON users (lower(username));
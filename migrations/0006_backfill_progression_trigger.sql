@@ -0,0 +1,14 @@
+CREATE OR REPLACE FUNCTION backfill_progression_for_skill() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO progression (user_id, skill_id, progression)
+    SELECT u.user_id, NEW.skill_id, 0.1
+    FROM users u
+    ON CONFLICT (user_id, skill_id) DO NOTHING;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS skills_backfill_progression ON skills;
+CREATE TRIGGER skills_backfill_progression
+    AFTER INSERT ON skills
+    FOR EACH ROW EXECUTE FUNCTION backfill_progression_for_skill();
@@ -0,0 +1,2 @@
+-- Audience of the client that opened the session; NULL means the default audience
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS audience TEXT;
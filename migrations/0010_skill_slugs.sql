@@ -0,0 +1,26 @@
+-- URL-safe identifier for each skill. It is derived from the display name once and never
+-- changes afterwards, so renaming a skill does not break clients that stored its slug.
+ALTER TABLE skills ADD COLUMN IF NOT EXISTS slug TEXT;
+
+CREATE OR REPLACE FUNCTION skill_slug(name TEXT) RETURNS TEXT AS $$
+    SELECT trim(both '-' from regexp_replace(lower(name), '[^a-z0-9]+', '-', 'g'));
+$$ LANGUAGE sql IMMUTABLE;
+
+UPDATE skills SET slug = skill_slug(skill_name) WHERE slug IS NULL;
+
+ALTER TABLE skills ALTER COLUMN slug SET NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS skills_slug_key ON skills (slug);
+
+CREATE OR REPLACE FUNCTION set_skill_slug() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.slug IS NULL THEN
+        NEW.slug := skill_slug(NEW.skill_name);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS skills_set_slug ON skills;
+CREATE TRIGGER skills_set_slug
+    BEFORE INSERT ON skills
+    FOR EACH ROW EXECUTE FUNCTION set_skill_slug();
@@ -0,0 +1,19 @@
+-- Starting progression for the skill backfill trigger, kept in step with
+-- knowledge_service::INITIAL_PROGRESSION: run_migrations writes the constant here on every start
+CREATE TABLE IF NOT EXISTS progression_settings (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    initial_progression DOUBLE PRECISION NOT NULL
+);
+
+INSERT INTO progression_settings (initial_progression) VALUES (0.1) ON CONFLICT (id) DO NOTHING;
+
+CREATE OR REPLACE FUNCTION backfill_progression_for_skill() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO progression (user_id, skill_id, progression)
+    SELECT u.user_id, NEW.skill_id, settings.initial_progression
+    FROM users u
+    CROSS JOIN progression_settings settings
+    ON CONFLICT (user_id, skill_id) DO NOTHING;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
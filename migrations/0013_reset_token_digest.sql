@@ -0,0 +1,5 @@
+-- Reset tokens are looked up by the SHA-256 digest of the token instead of verifying every
+-- outstanding argon2 hash. Tokens issued before this cannot be looked up, so expire them.
+UPDATE password_reset_tokens SET used_at = now() WHERE used_at IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS password_reset_tokens_token_hash_idx ON password_reset_tokens (token_hash);
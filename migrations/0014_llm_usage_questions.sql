@@ -0,0 +1,2 @@
+-- The word problem budget is charged per generated question rather than per request
+ALTER TABLE llm_usage RENAME COLUMN requests TO questions;
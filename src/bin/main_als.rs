@@ -0,0 +1,72 @@
+use als_api::{
+    config,
+    logging,
+    monitoring,
+    routes::{common, kt, question},
+    server,
+    services::database::{account::dummy_hash, database, login_attempts, migrations::run_migrations, signing_keys::{self, signing_key}},
+    state::AppState,
+};
+
+/// Serves the knowledge tracing and question routes from one process, for deployments
+/// that do not need to scale them separately.
+#[tokio::main]
+async fn main() {
+    let config = match config::init() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = logging::init(&config.logging) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+
+    if let Err(e) = monitoring::init() {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+
+    if let Err(e) = database::init(&config.database) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+
+    let state = match AppState::new(config.clone()) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = signing_keys::init(&config.jwt) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+
+    if let Err(e) = run_migrations().await {
+        eprintln!("Failed to run database migrations: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = signing_key() {
+        eprintln!("Failed to load JWT signing key: {e}");
+        std::process::exit(1);
+    }
+    if let Err(e) = dummy_hash() {
+        eprintln!("Failed to prepare login hashing: {e}");
+        std::process::exit(1);
+    }
+    login_attempts::prune_on_interval();
+
+    let routes = kt::router().merge(question::router());
+    let doc = common::api_doc([kt::openapi(), question::openapi()]);
+    let dependencies = kt::DEPENDENCIES.iter().chain(question::DEPENDENCIES);
+
+    let app = common::app(routes, doc, dependencies, state);
+
+    server::serve(app, &config.server).await.expect("Server failed");
+}
@@ -0,0 +1,25 @@
+use als_api::{config, services::database::{database, knowledge_service::backfill_progression}};
+
+#[tokio::main]
+async fn main() {
+    let config = match config::init() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = database::init(&config.database) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+
+    match backfill_progression().await {
+        Ok(inserted) => println!("Backfilled {inserted} missing progression rows"),
+        Err(e) => {
+            eprintln!("Failed to backfill progression: {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,610 @@
+use std::{fmt, net::SocketAddr, path::{Path, PathBuf}, str::FromStr, sync::Arc, time::Duration};
+
+use serde::Deserialize;
+
+use crate::{middleware::security::cors_layer, tls};
+
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("Failed to parse config file {0}: {1}")]
+    Parse(String, String),
+    #[error("Invalid value for {0}: {1}")]
+    Env(&'static str, String),
+    #[error("Invalid configuration: {0}")]
+    Invalid(String),
+}
+
+/// Everything the services need to run, loaded once at startup from a TOML file
+/// (`ALS_CONFIG`, or `config.toml` when present) with environment variables taking precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub jwt: JwtConfig,
+    pub password: PasswordConfig,
+    pub generator: GeneratorConfig,
+    pub llm: LlmConfig,
+    pub features: FeatureConfig,
+    pub logging: LoggingConfig,
+    pub rate_limits: RateLimitConfig,
+    pub cors: CorsConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_address: SocketAddr,
+    /// Take the client address from the last `X-Forwarded-For` entry; only safe behind a proxy that appends it
+    pub trust_proxy_headers: bool,
+    /// Seconds in-flight requests get to finish after SIGTERM or SIGINT
+    pub shutdown_timeout: u64,
+    /// Largest request body accepted, in bytes
+    pub body_limit: usize,
+    /// Seconds a request may take before it fails; keep it above `llm.timeout` so word problems can finish
+    pub request_timeout: u64,
+    /// PEM certificate chain to serve HTTPS with; set together with `tls_key_file`
+    pub tls_cert_file: Option<PathBuf>,
+    /// PEM private key for `tls_cert_file`
+    pub tls_key_file: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            trust_proxy_headers: false,
+            shutdown_timeout: 30,
+            body_limit: 1024 * 1024,
+            request_timeout: 150,
+            tls_cert_file: None,
+            tls_key_file: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// The certificate and key files when HTTPS is on.
+    pub fn tls(&self) -> Option<(&Path, &Path)> {
+        self.tls_cert_file.as_deref().zip(self.tls_key_file.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SslMode {
+    #[default]
+    Disable,
+    Prefer,
+    Require,
+}
+
+impl fmt::Display for SslMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for SslMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disable" => Ok(SslMode::Disable),
+            "prefer" => Ok(SslMode::Prefer),
+            "require" => Ok(SslMode::Require),
+            _ => Err(format!("Unknown sslmode: {s}")),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+    pub dbname: String,
+    /// Whether to connect over TLS; the server certificate is verified whenever TLS is used
+    pub sslmode: SslMode,
+    /// PEM CA certificates the server certificate must chain to; the public web roots when unset
+    pub ca_file: Option<PathBuf>,
+    /// Most connections held open at once
+    pub pool_size: usize,
+    /// Seconds to wait for a new connection, or for a free one when the pool is exhausted
+    pub connect_timeout: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 5432,
+            user: String::new(),
+            password: String::new(),
+            dbname: String::new(),
+            sslmode: SslMode::default(),
+            ca_file: None,
+            pool_size: 16,
+            connect_timeout: 5,
+        }
+    }
+}
+
+// Keeps the password out of logs and panic messages
+impl fmt::Debug for DatabaseConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DatabaseConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("user", &self.user)
+            .field("dbname", &self.dbname)
+            .field("sslmode", &self.sslmode)
+            .field("ca_file", &self.ca_file)
+            .field("pool_size", &self.pool_size)
+            .field("connect_timeout", &self.connect_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DatabaseConfig {
+    pub fn pg_config(&self) -> tokio_postgres::Config {
+        let mut pg_config = tokio_postgres::Config::new();
+        pg_config
+            .host(&self.host)
+            .port(self.port)
+            .user(&self.user)
+            .password(&self.password)
+            .dbname(&self.dbname)
+            .connect_timeout(Duration::from_secs(self.connect_timeout))
+            .ssl_mode(match self.sslmode {
+                SslMode::Disable => tokio_postgres::config::SslMode::Disable,
+                SslMode::Prefer => tokio_postgres::config::SslMode::Prefer,
+                SslMode::Require => tokio_postgres::config::SslMode::Require,
+            });
+        pg_config
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    pub issuer: String,
+    /// Audiences tokens may be issued for; the first is used when a client does not ask for one
+    pub audiences: Vec<String>,
+    /// Audiences of the clients teachers and admins work from, which staff routes accept
+    pub staff_audiences: Vec<String>,
+    /// Seconds
+    pub access_token_lifetime: u64,
+    /// Seconds of clock skew tolerated when checking `exp`
+    pub leeway: u64,
+    /// Ed25519 PKCS#8 PEM key tokens are signed with
+    pub signing_key: Option<PathBuf>,
+    /// Extra Ed25519 public keys tokens are accepted from, e.g. keys being rotated out
+    pub verification_keys: Vec<PathBuf>,
+    /// Where to fetch verification keys from when none are configured locally
+    pub jwks_url: Option<String>,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "knowledge tracing api".to_string(),
+            audiences: vec!["adapt math desktop-app".to_string(), "adapt math teacher-portal".to_string()],
+            staff_audiences: vec!["adapt math desktop-app".to_string(), "adapt math teacher-portal".to_string()],
+            access_token_lifetime: 15 * 60,
+            leeway: 60,
+            signing_key: None,
+            verification_keys: Vec::new(),
+            jwks_url: None,
+        }
+    }
+}
+
+impl JwtConfig {
+    /// The audience a client asked for, or the default when it did not ask.
+    pub fn resolve_audience(&self, requested: Option<&str>) -> Result<&str, String> {
+        match requested {
+            None => Ok(&self.audiences[0]),
+            Some(requested) => self.audiences
+                .iter()
+                .find(|audience| *audience == requested)
+                .map(String::as_str)
+                .ok_or_else(|| format!("Unknown audience {requested}")),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasswordConfig {
+    pub min_length: usize,
+    pub max_length: usize,
+    /// File of known-breached passwords, one per line
+    pub breached_list: Option<PathBuf>,
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        Self { min_length: 10, max_length: 128, breached_list: None }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneratorConfig {
+    pub url: String,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self { url: "http://172.18.0.12:5000".to_string() }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmConfig {
+    pub api_key: Option<String>,
+    pub url: String,
+    pub model: String,
+    /// Seconds
+    pub timeout: u64,
+    /// Word problems each user may generate per UTC day; 0 means unlimited
+    pub daily_budget: u32,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            url: "https://api.openai.com/v1/responses".to_string(),
+            model: "gpt-5-nano".to_string(),
+            timeout: 120,
+            daily_budget: 200,
+        }
+    }
+}
+
+impl fmt::Debug for LlmConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LlmConfig")
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("url", &self.url)
+            .field("model", &self.model)
+            .field("timeout", &self.timeout)
+            .field("daily_budget", &self.daily_budget)
+            .finish()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeatureConfig {
+    /// Allow anyone to create a student account through `/accounts/register`
+    pub registration: bool,
+    /// Rewrite generated questions as word problems through the LLM
+    pub word_problems: bool,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self { registration: true, word_problems: true }
+    }
+}
+
+/// Which browser origins may call the API.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Origins such as `https://portal.example.com`, or `*` for any; empty turns CORS off
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// Seconds browsers may cache a preflight response
+    pub max_age: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE"].map(String::from).to_vec(),
+            allowed_headers: ["authorization", "content-type", "x-request-id"].map(String::from).to_vec(),
+            max_age: 600,
+        }
+    }
+}
+
+/// A token bucket: `burst` requests at once, refilled at `per_minute`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    /// 0 turns the limit off
+    pub per_minute: u32,
+    pub burst: u32,
+}
+
+/// Parses `<per_minute>,<burst>`, the form the `RATE_LIMIT_*` variables take.
+impl FromStr for RateLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (per_minute, burst) = s.split_once(',')
+            .ok_or_else(|| format!("Expected <per_minute>,<burst>, got {s}"))?;
+        Ok(Self {
+            per_minute: per_minute.trim().parse().map_err(|e| format!("Invalid per_minute {per_minute}: {e}"))?,
+            burst: burst.trim().parse().map_err(|e| format!("Invalid burst {burst}: {e}"))?,
+        })
+    }
+}
+
+/// Limits per route group, counted per user, or per client IP for routes used before login.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Login, token refresh, registration and password reset
+    pub auth: RateLimit,
+    /// Answer submission
+    pub answers: RateLimit,
+    /// Question generation
+    pub generation: RateLimit,
+    /// Word problem generation, which also calls the LLM
+    pub word_generation: RateLimit,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            auth: RateLimit { per_minute: 10, burst: 5 },
+            answers: RateLimit { per_minute: 120, burst: 30 },
+            generation: RateLimit { per_minute: 60, burst: 20 },
+            word_generation: RateLimit { per_minute: 6, burst: 3 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable, for local development
+    #[default]
+    Pretty,
+    /// One JSON object per line, for log shippers
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    /// `tracing` filter directives, e.g. `info` or `info,als_api::services=debug`
+    pub filter: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self { format: LogFormat::default(), filter: "info".to_string() }
+    }
+}
+
+fn override_from_env<T: FromStr>(name: &'static str, target: &mut T) -> Result<(), ConfigError>
+where
+    T::Err: fmt::Display,
+{
+    if let Ok(value) = std::env::var(name) {
+        *target = value.parse().map_err(|e: T::Err| ConfigError::Env(name, e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn override_list_from_env<T: From<String>>(name: &'static str, target: &mut Vec<T>) {
+    if let Ok(value) = std::env::var(name) {
+        *target = value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| T::from(item.to_string()))
+            .collect();
+    }
+}
+
+fn override_optional_from_env<T: From<String>>(name: &'static str, target: &mut Option<T>) {
+    if let Ok(value) = std::env::var(name) {
+        *target = Some(T::from(value));
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match std::env::var("ALS_CONFIG") {
+            Ok(path) => Self::from_file(&path)?,
+            Err(_) if std::path::Path::new(DEFAULT_CONFIG_PATH).exists() => Self::from_file(DEFAULT_CONFIG_PATH)?,
+            Err(_) => Self::default(),
+        };
+
+        config.apply_env()?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    fn from_file(path: &str) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Read(path.to_string(), e))?;
+        toml::from_str(&contents).map_err(|e| ConfigError::Parse(path.to_string(), e.to_string()))
+    }
+
+    /// Environment variables override the file, keeping the names the services have always read.
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        override_from_env("BIND_ADDRESS", &mut self.server.bind_address)?;
+        override_from_env("TRUST_PROXY_HEADERS", &mut self.server.trust_proxy_headers)?;
+        override_from_env("SHUTDOWN_TIMEOUT", &mut self.server.shutdown_timeout)?;
+        override_from_env("BODY_LIMIT", &mut self.server.body_limit)?;
+        override_from_env("REQUEST_TIMEOUT", &mut self.server.request_timeout)?;
+        override_optional_from_env("TLS_CERT_FILE", &mut self.server.tls_cert_file);
+        override_optional_from_env("TLS_KEY_FILE", &mut self.server.tls_key_file);
+
+        override_list_from_env("CORS_ALLOWED_ORIGINS", &mut self.cors.allowed_origins);
+        override_list_from_env("CORS_ALLOWED_METHODS", &mut self.cors.allowed_methods);
+        override_list_from_env("CORS_ALLOWED_HEADERS", &mut self.cors.allowed_headers);
+        override_from_env("CORS_MAX_AGE", &mut self.cors.max_age)?;
+
+        override_from_env("POSTGRES_IP", &mut self.database.host)?;
+        override_from_env("POSTGRES_PORT", &mut self.database.port)?;
+        override_from_env("POSTGRES_USER", &mut self.database.user)?;
+        override_from_env("POSTGRES_PASSWORD", &mut self.database.password)?;
+        override_from_env("POSTGRES_DB", &mut self.database.dbname)?;
+        override_from_env("POSTGRES_SSLMODE", &mut self.database.sslmode)?;
+        override_optional_from_env("POSTGRES_CA_FILE", &mut self.database.ca_file);
+        override_from_env("POSTGRES_POOL_SIZE", &mut self.database.pool_size)?;
+        override_from_env("POSTGRES_CONNECT_TIMEOUT", &mut self.database.connect_timeout)?;
+
+        override_from_env("JWT_ISSUER", &mut self.jwt.issuer)?;
+        override_list_from_env("JWT_AUDIENCES", &mut self.jwt.audiences);
+        override_list_from_env("JWT_STAFF_AUDIENCES", &mut self.jwt.staff_audiences);
+        override_from_env("JWT_ACCESS_TOKEN_LIFETIME", &mut self.jwt.access_token_lifetime)?;
+        override_from_env("JWT_LEEWAY", &mut self.jwt.leeway)?;
+        override_optional_from_env("JWT_SIGNING_KEY", &mut self.jwt.signing_key);
+        override_list_from_env("JWT_VERIFICATION_KEYS", &mut self.jwt.verification_keys);
+        override_optional_from_env("JWKS_URL", &mut self.jwt.jwks_url);
+
+        override_from_env("PASSWORD_MIN_LENGTH", &mut self.password.min_length)?;
+        override_from_env("PASSWORD_MAX_LENGTH", &mut self.password.max_length)?;
+        override_optional_from_env("PASSWORD_BREACHED_LIST", &mut self.password.breached_list);
+
+        override_from_env("GENERATOR_URL", &mut self.generator.url)?;
+
+        override_optional_from_env("OPENAI_API_KEY", &mut self.llm.api_key);
+        override_from_env("OPENAI_URL", &mut self.llm.url)?;
+        override_from_env("OPENAI_MODEL", &mut self.llm.model)?;
+        override_from_env("OPENAI_TIMEOUT", &mut self.llm.timeout)?;
+        override_from_env("OPENAI_DAILY_BUDGET", &mut self.llm.daily_budget)?;
+
+        override_from_env("FEATURE_REGISTRATION", &mut self.features.registration)?;
+        override_from_env("FEATURE_WORD_PROBLEMS", &mut self.features.word_problems)?;
+
+        override_from_env("LOG_FORMAT", &mut self.logging.format)?;
+        override_from_env("RUST_LOG", &mut self.logging.filter)?;
+
+        let rate_limits = [
+            ("RATE_LIMIT_AUTH", &mut self.rate_limits.auth),
+            ("RATE_LIMIT_ANSWERS", &mut self.rate_limits.answers),
+            ("RATE_LIMIT_GENERATION", &mut self.rate_limits.generation),
+            ("RATE_LIMIT_WORD_GENERATION", &mut self.rate_limits.word_generation),
+        ];
+        for (name, limit) in rate_limits {
+            override_from_env(name, limit)?;
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let required = [
+            ("database.host (POSTGRES_IP)", &self.database.host),
+            ("database.user (POSTGRES_USER)", &self.database.user),
+            ("database.dbname (POSTGRES_DB)", &self.database.dbname),
+            ("jwt.issuer (JWT_ISSUER)", &self.jwt.issuer),
+            ("generator.url (GENERATOR_URL)", &self.generator.url),
+            ("llm.url (OPENAI_URL)", &self.llm.url),
+            ("llm.model (OPENAI_MODEL)", &self.llm.model),
+        ];
+        for (name, value) in required {
+            if value.trim().is_empty() {
+                return Err(ConfigError::Invalid(format!("{name} must be set")));
+            }
+        }
+
+        if self.jwt.audiences.is_empty() {
+            return Err(ConfigError::Invalid("jwt.audiences (JWT_AUDIENCES) must list at least one audience".to_string()));
+        }
+        if let Some(audience) = self.jwt.staff_audiences.iter().find(|audience| !self.jwt.audiences.contains(audience)) {
+            return Err(ConfigError::Invalid(format!(
+                "jwt.staff_audiences (JWT_STAFF_AUDIENCES) includes {audience}, which is not in jwt.audiences; set both together"
+            )));
+        }
+        let rate_limits = [
+            ("rate_limits.auth", self.rate_limits.auth),
+            ("rate_limits.answers", self.rate_limits.answers),
+            ("rate_limits.generation", self.rate_limits.generation),
+            ("rate_limits.word_generation", self.rate_limits.word_generation),
+        ];
+        for (name, limit) in rate_limits {
+            if limit.per_minute > 0 && limit.burst == 0 {
+                return Err(ConfigError::Invalid(format!("{name}.burst must be positive while the limit is on")));
+            }
+        }
+        if self.server.request_timeout == 0 {
+            return Err(ConfigError::Invalid("server.request_timeout must be positive".to_string()));
+        }
+        cors_layer(&self.cors).map_err(ConfigError::Invalid)?;
+        match (&self.server.tls_cert_file, &self.server.tls_key_file) {
+            (Some(cert_file), Some(key_file)) => {
+                tls::server_config(cert_file, key_file).map_err(ConfigError::Invalid)?;
+            }
+            (None, None) => {}
+            _ => return Err(ConfigError::Invalid(
+                "server.tls_cert_file (TLS_CERT_FILE) and server.tls_key_file (TLS_KEY_FILE) must be set together".to_string()
+            )),
+        }
+        if self.database.pool_size == 0 {
+            return Err(ConfigError::Invalid("database.pool_size must be positive".to_string()));
+        }
+        if self.database.sslmode != SslMode::Disable {
+            tls::postgres_connector(self.database.ca_file.as_deref()).map_err(ConfigError::Invalid)?;
+        } else if self.database.ca_file.is_some() {
+            return Err(ConfigError::Invalid("database.ca_file (POSTGRES_CA_FILE) needs sslmode prefer or require".to_string()));
+        }
+        if self.jwt.access_token_lifetime == 0 {
+            return Err(ConfigError::Invalid("jwt.access_token_lifetime must be positive".to_string()));
+        }
+        if self.password.min_length == 0 || self.password.min_length > self.password.max_length {
+            return Err(ConfigError::Invalid(format!(
+                "password.min_length ({}) must be between 1 and password.max_length ({})",
+                self.password.min_length, self.password.max_length
+            )));
+        }
+
+        let urls = [
+            ("generator.url", Some(&self.generator.url)),
+            ("llm.url", Some(&self.llm.url)),
+            ("jwt.jwks_url", self.jwt.jwks_url.as_ref()),
+        ];
+        for (name, url) in urls {
+            if let Some(url) = url && reqwest::Url::parse(url).is_err() {
+                return Err(ConfigError::Invalid(format!("{name} is not a valid URL: {url}")));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads and validates the configuration; binaries call this first so mistakes stop startup,
+/// then hand it to the services they set up and to the router state.
+pub fn init() -> Result<Arc<Config>, ConfigError> {
+    Config::load().map(Arc::new)
+}
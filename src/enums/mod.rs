@@ -1 +1,4 @@
-pub mod difficulty;
\ No newline at end of file
+pub mod dependency;
+pub mod difficulty;
+pub mod health_status;
+pub mod role;
\ No newline at end of file
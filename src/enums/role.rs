@@ -0,0 +1,43 @@
+use std::{fmt, str::FromStr};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Ordered from least to most privileged, so a higher role satisfies any lower requirement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Student,
+    Teacher,
+    Admin
+}
+
+impl Role {
+    pub fn satisfies(self, required: Role) -> bool {
+        self >= required
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Role::Student => "student",
+            Role::Teacher => "teacher",
+            Role::Admin => "admin",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "student" => Ok(Role::Student),
+            "teacher" => Ok(Role::Teacher),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("Unknown role: {other}")),
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt::Display;
+
+use axum::{
+    Json,
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::{StatusCode, header::RETRY_AFTER},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    middleware::request_id::current_request_id,
+    services::{
+        database::{
+            account::AccountError,
+            assignment_service::AssignmentError,
+            class_service::ClassError,
+            knowledge_service::KnowledgeError,
+            question_service::GeneratorError as ModuleError,
+        },
+        generator::modules::GeneratorError,
+    },
+};
+
+/// Body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Stable machine-readable code, e.g. `not_found` or `database_unavailable`
+    pub code: &'static str,
+    pub message: String,
+    pub request_id: Option<String>,
+    /// Close matches when a named resource such as a skill does not exist
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+}
+
+/// An error returned from a handler. Internal details are logged against the request id
+/// and never sent to the client.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    retry_after: Option<u64>,
+    suggestions: Vec<String>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into(), retry_after: None, suggestions: Vec::new() }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "validation_failed", message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "forbidden", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, "conflict", message)
+    }
+
+    /// A dependency such as the database or generator is down.
+    pub fn unavailable(code: &'static str, message: impl Into<String>, detail: impl Display) -> Self {
+        log_detail(code, &detail);
+        Self::new(StatusCode::SERVICE_UNAVAILABLE, code, message)
+    }
+
+    pub fn internal(detail: impl Display) -> Self {
+        log_detail("internal_error", &detail);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", "An unexpected error occurred")
+    }
+
+    pub fn with_retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
+
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+}
+
+// The request id is on the enclosing `http_request` span
+fn log_detail(code: &str, detail: &dyn Display) {
+    tracing::error!(code, "{detail}");
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            code: self.code,
+            message: self.message,
+            request_id: current_request_id(),
+            suggestions: self.suggestions,
+        };
+
+        let mut response = (self.status, Json(body)).into_response();
+        if let Some(seconds) = self.retry_after {
+            response.headers_mut().insert(RETRY_AFTER, seconds.into());
+        }
+        response
+    }
+}
+
+fn database_unavailable(detail: impl Display) -> ApiError {
+    ApiError::unavailable("database_unavailable", "The database is unavailable", detail)
+}
+
+impl From<AccountError> for ApiError {
+    fn from(error: AccountError) -> Self {
+        match error {
+            AccountError::Authentication(message) => ApiError::new(StatusCode::UNAUTHORIZED, "authentication_failed", message),
+            AccountError::InvalidToken(_) => ApiError::new(StatusCode::UNAUTHORIZED, "invalid_token", "Invalid or expired token"),
+            AccountError::Validation(message) => ApiError::validation(message),
+            AccountError::NotFound(message) => ApiError::not_found(message),
+            AccountError::Conflict(message) => ApiError::conflict(message),
+            AccountError::Locked(retry_after) => ApiError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "too_many_attempts",
+                "Too many failed login attempts"
+            ).with_retry_after(retry_after),
+            AccountError::Unavailable(detail) => database_unavailable(detail),
+            e @ (AccountError::Database(_) | AccountError::Hashing(_) | AccountError::TokenCreation(_) | AccountError::Other(_)) => {
+                ApiError::internal(e)
+            },
+        }
+    }
+}
+
+impl From<KnowledgeError> for ApiError {
+    fn from(error: KnowledgeError) -> Self {
+        match error {
+            KnowledgeError::InvalidInput(message) => ApiError::validation(message),
+            KnowledgeError::SkillNotFound { name, suggestions } => {
+                let message = match suggestions.as_slice() {
+                    [] => format!("Unknown skill '{name}'"),
+                    [only] => format!("Unknown skill '{name}'. Did you mean '{only}'?"),
+                    _ => format!("Unknown skill '{name}'. Did you mean one of: {}?", suggestions.join(", ")),
+                };
+                ApiError::new(StatusCode::NOT_FOUND, "skill_not_found", message).with_suggestions(suggestions)
+            },
+            KnowledgeError::ProgressionNotFound { name } => ApiError::new(
+                StatusCode::NOT_FOUND,
+                "progression_not_found",
+                format!("No progression recorded for skill '{name}'")
+            ),
+            KnowledgeError::Unavailable(detail) => database_unavailable(detail),
+            e @ (KnowledgeError::Database(_) | KnowledgeError::Other(_)) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<ClassError> for ApiError {
+    fn from(error: ClassError) -> Self {
+        match error {
+            ClassError::NotFound(message) => ApiError::not_found(message),
+            ClassError::Validation(message) => ApiError::validation(message),
+            ClassError::Unavailable(detail) => database_unavailable(detail),
+            e @ (ClassError::Database(_) | ClassError::Other(_)) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<AssignmentError> for ApiError {
+    fn from(error: AssignmentError) -> Self {
+        match error {
+            AssignmentError::NotFound(message) => ApiError::not_found(message),
+            AssignmentError::Validation(message) => ApiError::validation(message),
+            AssignmentError::Unavailable(detail) => database_unavailable(detail),
+            e @ (AssignmentError::Database(_) | AssignmentError::Other(_)) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<ModuleError> for ApiError {
+    fn from(error: ModuleError) -> Self {
+        match error {
+            ModuleError::Authentication(message) => ApiError::unauthorized(message),
+            ModuleError::Validation(message) => ApiError::validation(message),
+            ModuleError::Unavailable(detail) => database_unavailable(detail),
+            ModuleError::BudgetExhausted(retry_after) => ApiError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "llm_budget_exhausted",
+                "Daily word problem limit reached"
+            ).with_retry_after(retry_after),
+            e @ (ModuleError::Database(_) | ModuleError::Other(_)) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GeneratorError> for ApiError {
+    fn from(error: GeneratorError) -> Self {
+        match error {
+            GeneratorError::Connection(detail) => {
+                ApiError::unavailable("generator_unavailable", "The question generator is unavailable", detail)
+            },
+            GeneratorError::GPT(detail) => {
+                ApiError::unavailable("llm_unavailable", "The word problem service is unavailable", detail)
+            },
+            e @ GeneratorError::Other(_) => ApiError::internal(e),
+        }
+    }
+}
+
+fn invalid_request(status: StatusCode, message: String) -> ApiError {
+    ApiError::new(status, "invalid_request", message)
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        invalid_request(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        invalid_request(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        invalid_request(rejection.status(), rejection.body_text())
+    }
+}
@@ -0,0 +1,25 @@
+const MIN_SIMILARITY: f64 = 0.8;
+
+/// Picks up to `limit` candidates that look like a misspelling of `query`, best match first.
+/// Matching ignores case, and a candidate containing the query (or vice versa) always qualifies.
+pub fn closest_matches<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>, limit: usize) -> Vec<String> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut scored: Vec<(f64, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_lowercase();
+            let mut score = strsim::jaro_winkler(&query, &lower);
+            if lower.contains(&query) || query.contains(&lower) {
+                score = score.max(MIN_SIMILARITY);
+            }
+            (score >= MIN_SIMILARITY).then_some((score, candidate))
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    scored.into_iter().take(limit).map(|(_, candidate)| candidate.to_string()).collect()
+}
@@ -1 +1,5 @@
-pub mod topic_conversion;
\ No newline at end of file
+pub mod topic_conversion;
+pub mod password_policy;
+pub mod username;
+pub mod fuzzy_match;
+pub mod slug;
\ No newline at end of file
@@ -0,0 +1,44 @@
+use std::collections::HashSet;
+
+use crate::config::PasswordConfig;
+
+/// Password strength rules from the `[password]` section of the configuration.
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    breached: HashSet<String>,
+}
+
+impl PasswordPolicy {
+    /// Builds the policy, reading the breached password list when one is configured.
+    pub fn from_config(config: &PasswordConfig) -> Result<Self, String> {
+        let breached = match &config.breached_list {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read breached password list {}: {e}", path.display()))?
+                .lines()
+                .map(|line| line.trim().to_lowercase())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            None => HashSet::new(),
+        };
+
+        Ok(Self { min_length: config.min_length, max_length: config.max_length, breached })
+    }
+
+    pub fn check(&self, password: &str, username: &str) -> Result<(), String> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(format!("Password must be at least {} characters", self.min_length));
+        }
+        if length > self.max_length {
+            return Err(format!("Password must be at most {} characters", self.max_length));
+        }
+        if password.to_lowercase() == username.to_lowercase() {
+            return Err("Password must not match the username".to_string());
+        }
+        if self.breached.contains(&password.to_lowercase()) {
+            return Err("Password appears in a list of breached passwords".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,9 @@
+/// Lowercases `name` and joins its alphanumeric runs with `-`, matching the
+/// `skill_slug` function the database uses to derive skill slugs.
+pub fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
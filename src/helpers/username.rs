@@ -0,0 +1,21 @@
+const MIN_LENGTH: usize = 3;
+const MAX_LENGTH: usize = 32;
+
+/// Normalises a username to the form stored in `users`: trimmed and lowercase,
+/// 3 to 32 characters of `a-z`, `0-9`, `.`, `_` or `-`, starting with a letter or digit.
+pub fn normalise_username(input: &str) -> Result<String, String> {
+    let username = input.trim().to_lowercase();
+    let length = username.chars().count();
+
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&length) {
+        return Err(format!("Username must be between {MIN_LENGTH} and {MAX_LENGTH} characters"));
+    }
+    if !username.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-')) {
+        return Err("Username may only contain letters, digits, '.', '_' and '-'".to_string());
+    }
+    if !username.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Err("Username must start with a letter or digit".to_string());
+    }
+
+    Ok(username)
+}
@@ -2,4 +2,13 @@ pub mod services;
 pub mod structs;
 pub mod enums;
 pub mod middleware;
-pub mod helpers;
\ No newline at end of file
+pub mod helpers;
+pub mod config;
+pub mod state;
+pub mod error;
+pub mod routes;
+pub mod logging;
+pub mod monitoring;
+pub mod server;
+pub mod shutdown;
+pub mod tls;
\ No newline at end of file
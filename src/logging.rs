@@ -0,0 +1,21 @@
+use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
+
+use crate::config::{LogFormat, LoggingConfig};
+
+/// Installs the global subscriber. Spans are logged when they close, so every request,
+/// database, generator and LLM span reports how long it took.
+pub fn init(logging: &LoggingConfig) -> Result<(), String> {
+    let filter = EnvFilter::try_new(&logging.filter)
+        .map_err(|e| format!("Invalid value for logging.filter (RUST_LOG): {e}"))?;
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE);
+
+    let result = match logging.format {
+        LogFormat::Pretty => builder.try_init(),
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(true).try_init(),
+    };
+
+    result.map_err(|e| format!("Failed to install log subscriber: {e}"))
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts},
     http::{request::Parts, StatusCode},
     RequestPartsExt,
 };
@@ -7,8 +7,12 @@ use axum_extra::{
     headers::{authorization::Bearer, Authorization},
     TypedHeader,
 };
+use std::{marker::PhantomData, sync::Arc};
 use crate::{
-    services::database::jwt::validate_jwt,
+    config::{Config, JwtConfig},
+    enums::role::Role,
+    error::ApiError,
+    services::database::{account::AccountError, jwt::validate_jwt},
     structs::claims::Claims,
 };
 
@@ -19,24 +23,102 @@ pub struct AuthenticatedUser {
 impl<S> FromRequestParts<S> for AuthenticatedUser
 where
     S: Send + Sync,
+    Arc<Config>: FromRef<S>,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = ApiError;
 
     async fn from_request_parts(
         parts: &mut Parts,
-        _state: &S,
+        state: &S,
     ) -> Result<Self, Self::Rejection> {
+        let config = Arc::<Config>::from_ref(state);
+
         let TypedHeader(Authorization(bearer)) = parts
             .extract::<TypedHeader<Authorization<Bearer>>>()
             .await
-            .map_err(|_| (StatusCode::UNAUTHORIZED, "Missing authorization header".to_string()))?;
+            .map_err(|_| ApiError::new(StatusCode::UNAUTHORIZED, "missing_credentials", "Missing authorization header"))?;
 
-        let jwt_secret = std::env::var("JWT_SECRET")
-            .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "JWT secret not configured".to_string()))?;
+        let claims = validate_jwt(bearer.token(), &config.jwt)
+            .await
+            .map_err(|e| match e {
+                AccountError::InvalidToken(_) => ApiError::from(e),
+                e => ApiError::unavailable("signing_keys_unavailable", "Unable to verify token", e),
+            })?;
 
-        let claims = validate_jwt(bearer.token(), &jwt_secret)
-            .map_err(|e| (StatusCode::UNAUTHORIZED, format!("Invalid or expired token {e}")))?;
+        tracing::Span::current().record("user_id", claims.uid);
 
         Ok(Self { claims })
     }
-}
\ No newline at end of file
+}
+/// Marker for the minimum role a route requires, used with [`AuthorizedUser`].
+pub trait RequiredRole {
+    const ROLE: Role;
+}
+
+pub struct Teacher;
+
+impl RequiredRole for Teacher {
+    const ROLE: Role = Role::Teacher;
+}
+
+pub struct Admin;
+
+impl RequiredRole for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+/// Marker for the clients a route accepts tokens from, used with [`AuthorizedUser`].
+pub trait RequiredAudience {
+    fn allows(audience: &str, jwt: &JwtConfig) -> bool;
+}
+
+/// Any audience the service is configured to accept.
+pub struct AnyClient;
+
+impl RequiredAudience for AnyClient {
+    fn allows(_audience: &str, _jwt: &JwtConfig) -> bool {
+        true
+    }
+}
+
+/// Clients teachers and admins work from, as listed in `jwt.staff_audiences`.
+pub struct StaffClient;
+
+impl RequiredAudience for StaffClient {
+    fn allows(audience: &str, jwt: &JwtConfig) -> bool {
+        jwt.staff_audiences.iter().any(|staff_audience| staff_audience == audience)
+    }
+}
+
+/// An authenticated user whose role is at least `R`, holding a token issued for a client
+/// `A` accepts, e.g. `AuthorizedUser<Admin, StaffClient>`.
+pub struct AuthorizedUser<R: RequiredRole, A: RequiredAudience = AnyClient> {
+    pub claims: Claims,
+    _marker: PhantomData<fn() -> (R, A)>,
+}
+
+impl<S, R, A> FromRequestParts<S> for AuthorizedUser<R, A>
+where
+    S: Send + Sync,
+    Arc<Config>: FromRef<S>,
+    R: RequiredRole,
+    A: RequiredAudience,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let AuthenticatedUser { claims } = AuthenticatedUser::from_request_parts(parts, state).await?;
+
+        if !claims.role.satisfies(R::ROLE) {
+            return Err(ApiError::forbidden(format!("Requires {} role", R::ROLE)));
+        }
+        if !A::allows(&claims.aud, &Arc::<Config>::from_ref(state).jwt) {
+            return Err(ApiError::forbidden(format!("Tokens for {} cannot access this route", claims.aud)));
+        }
+
+        Ok(Self { claims, _marker: PhantomData })
+    }
+}
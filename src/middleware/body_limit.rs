@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{config::Config, error::ApiError};
+
+/// Turns every `413` into the same `payload_too_large` error, whether the body limit rejected
+/// the request before the handler ran or an extractor read past it.
+pub async fn payload_too_large(State(config): State<Arc<Config>>, request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE {
+        return response;
+    }
+
+    ApiError::new(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "payload_too_large",
+        format!("The request body is larger than {} bytes", config.server.body_limit),
+    ).into_response()
+}
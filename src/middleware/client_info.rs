@@ -0,0 +1,55 @@
+use std::{convert::Infallible, net::{IpAddr, SocketAddr}, sync::Arc};
+
+use axum::{
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts},
+};
+
+use crate::config::Config;
+
+/// Details about the device making the request, recorded against sessions.
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+    Arc<Config>: FromRef<S>,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let user_agent = parts.headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
+        // Only trust X-Forwarded-For when we are deployed behind a proxy that sets it. Proxies
+        // append the address they saw, so the last entry is the only one the client cannot forge.
+        let forwarded_for = if Arc::<Config>::from_ref(state).server.trust_proxy_headers {
+            parts.headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .next_back()
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit(',').next())
+                .and_then(|value| value.trim().parse::<IpAddr>().ok())
+                .map(|ip| ip.to_string())
+        } else {
+            None
+        };
+
+        let ip_address = forwarded_for.or_else(|| {
+            parts.extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        });
+
+        Ok(Self { user_agent, ip_address })
+    }
+}
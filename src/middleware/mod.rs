@@ -1 +1,10 @@
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod body_limit;
+pub mod client_info;
+pub mod extract;
+pub mod http_metrics;
+pub mod rate_limit;
+pub mod request_id;
+pub mod security;
+pub mod timeout;
+pub mod versioning;
\ No newline at end of file
@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{StatusCode, request::Parts},
+};
+use metrics::counter;
+
+use crate::{
+    config::{Config, RateLimit, RateLimitConfig},
+    error::ApiError,
+    middleware::{auth::AuthenticatedUser, client_info::ClientInfo},
+};
+
+/// Marker for the route group a [`RateLimited`] extractor counts against.
+pub trait RouteGroup {
+    const NAME: &'static str;
+
+    fn limit(limits: &RateLimitConfig) -> RateLimit;
+}
+
+/// Login, token refresh, registration and password changes.
+pub struct AuthRoutes;
+
+impl RouteGroup for AuthRoutes {
+    const NAME: &'static str = "auth";
+
+    fn limit(limits: &RateLimitConfig) -> RateLimit {
+        limits.auth
+    }
+}
+
+pub struct AnswerRoutes;
+
+impl RouteGroup for AnswerRoutes {
+    const NAME: &'static str = "answers";
+
+    fn limit(limits: &RateLimitConfig) -> RateLimit {
+        limits.answers
+    }
+}
+
+pub struct GenerationRoutes;
+
+impl RouteGroup for GenerationRoutes {
+    const NAME: &'static str = "generation";
+
+    fn limit(limits: &RateLimitConfig) -> RateLimit {
+        limits.generation
+    }
+}
+
+pub struct WordGenerationRoutes;
+
+impl RouteGroup for WordGenerationRoutes {
+    const NAME: &'static str = "word_generation";
+
+    fn limit(limits: &RateLimitConfig) -> RateLimit {
+        limits.word_generation
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    /// When the bucket will be back to `burst`, after which it can be forgotten
+    full_at: Instant,
+}
+
+impl Bucket {
+    /// Takes a token, or says how long until one is available.
+    fn take(&mut self, limit: RateLimit, now: Instant) -> Result<(), Duration> {
+        let per_second = f64::from(limit.per_minute) / 60.0;
+        let burst = f64::from(limit.burst);
+
+        let refilled = now.duration_since(self.refilled_at).as_secs_f64() * per_second;
+        self.tokens = (self.tokens + refilled).min(burst);
+        self.refilled_at = now;
+
+        let result = if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second))
+        };
+
+        self.full_at = now + Duration::from_secs_f64((burst - self.tokens) / per_second);
+        result
+    }
+}
+
+/// Full buckets are dropped once this many clients are being tracked.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+static BUCKETS: LazyLock<Mutex<HashMap<(&'static str, String), Bucket>>> = LazyLock::new(Default::default);
+
+fn take_token(group: &'static str, key: String, limit: RateLimit) -> Result<(), Duration> {
+    let now = Instant::now();
+    let mut buckets = BUCKETS.lock().unwrap_or_else(|e| e.into_inner());
+
+    if buckets.len() >= PRUNE_THRESHOLD {
+        buckets.retain(|_, bucket| bucket.full_at > now);
+    }
+
+    buckets
+        .entry((group, key))
+        .or_insert_with(|| Bucket { tokens: f64::from(limit.burst), refilled_at: now, full_at: now })
+        .take(limit, now)
+}
+
+/// Who a request counts against: the user when it carries a valid token, otherwise the client IP.
+async fn client_key<S>(parts: &mut Parts, state: &S) -> String
+where
+    S: Send + Sync,
+    Arc<Config>: FromRef<S>,
+{
+    if let Ok(AuthenticatedUser { claims }) = AuthenticatedUser::from_request_parts(parts, state).await {
+        return format!("user:{}", claims.uid);
+    }
+
+    let Ok(client_info) = ClientInfo::from_request_parts(parts, state).await;
+    format!("ip:{}", client_info.ip_address.unwrap_or_default())
+}
+
+/// Takes a token from the caller's bucket for route group `G`, rejecting the request with
+/// `429 Too Many Requests` and a `Retry-After` when the bucket is empty.
+pub struct RateLimited<G: RouteGroup> {
+    _marker: PhantomData<fn() -> G>,
+}
+
+impl<S, G> FromRequestParts<S> for RateLimited<G>
+where
+    S: Send + Sync,
+    Arc<Config>: FromRef<S>,
+    G: RouteGroup,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let limit = G::limit(&Arc::<Config>::from_ref(state).rate_limits);
+
+        if limit.per_minute > 0 {
+            let key = client_key(parts, state).await;
+            take_token(G::NAME, key, limit).map_err(|wait| {
+                counter!("rate_limited_total", "group" => G::NAME).increment(1);
+                ApiError::new(StatusCode::TOO_MANY_REQUESTS, "rate_limited", "Too many requests, slow down")
+                    .with_retry_after(wait.as_secs_f64().ceil().max(1.0) as u64)
+            })?;
+        }
+
+        Ok(Self { _marker: PhantomData })
+    }
+}
@@ -0,0 +1,89 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Request, State},
+    http::{
+        HeaderName, HeaderValue, Method,
+        header::{
+            CONTENT_SECURITY_POLICY, LINK, REFERRER_POLICY, RETRY_AFTER, STRICT_TRANSPORT_SECURITY,
+            X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+        },
+    },
+    middleware::Next,
+    response::Response,
+};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::{
+    config::{Config, CorsConfig},
+    middleware::{request_id::X_REQUEST_ID, versioning::{DEPRECATION, SUNSET}},
+};
+
+/// Response headers browsers are told to stay safe with. The API serves no documents of its own,
+/// so nothing may frame it, sniff it or leak its URLs as a referrer.
+const SECURITY_HEADERS: [(HeaderName, &str); 4] = [
+    (X_CONTENT_TYPE_OPTIONS, "nosniff"),
+    (X_FRAME_OPTIONS, "DENY"),
+    (REFERRER_POLICY, "no-referrer"),
+    (CONTENT_SECURITY_POLICY, "frame-ancestors 'none'"),
+];
+
+/// Tells browsers to keep using HTTPS. Only sent when this server terminates TLS itself,
+/// since browsers ignore it over plain HTTP.
+const HSTS: &str = "max-age=31536000; includeSubDomains";
+
+/// Adds [`SECURITY_HEADERS`], and [`HSTS`] when serving HTTPS, to every response that does
+/// not set them itself.
+pub async fn security_headers(State(config): State<Arc<Config>>, request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    let hsts = config.server.tls().is_some().then_some((STRICT_TRANSPORT_SECURITY, HSTS));
+    for (name, value) in SECURITY_HEADERS.into_iter().chain(hsts) {
+        if !headers.contains_key(&name) {
+            headers.insert(name, HeaderValue::from_static(value));
+        }
+    }
+
+    response
+}
+
+/// Builds the CORS layer, or `None` when no origins are allowed.
+pub fn cors_layer(cors: &CorsConfig) -> Result<Option<CorsLayer>, String> {
+    if cors.allowed_origins.is_empty() {
+        return Ok(None);
+    }
+
+    let origins = if cors.allowed_origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::from(Any)
+    } else {
+        let origins = cors.allowed_origins
+            .iter()
+            .map(|origin| HeaderValue::from_str(origin).map_err(|e| format!("Invalid CORS origin {origin}: {e}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    let methods = cors.allowed_methods
+        .iter()
+        .map(|method| method.parse::<Method>().map_err(|e| format!("Invalid CORS method {method}: {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let headers = cors.allowed_headers
+        .iter()
+        .map(|header| header.parse::<HeaderName>().map_err(|e| format!("Invalid CORS header {header}: {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .expose_headers([
+                X_REQUEST_ID.clone(),
+                RETRY_AFTER,
+                LINK,
+                DEPRECATION.clone(),
+                SUNSET.clone(),
+            ])
+            .max_age(Duration::from_secs(cors.max_age)),
+    ))
+}
@@ -0,0 +1,29 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{config::Config, error::ApiError};
+
+/// How long clients are asked to wait before retrying a request that timed out.
+const RETRY_AFTER_SECONDS: u64 = 5;
+
+/// Fails requests that run past `server.request_timeout` with `503 request_timeout`, asking
+/// clients to retry after [`RETRY_AFTER_SECONDS`].
+pub async fn request_timeout(State(config): State<Arc<Config>>, request: Request, next: Next) -> Response {
+    let timeout = Duration::from_secs(config.server.request_timeout);
+
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!("Request did not finish within {}s", timeout.as_secs());
+            ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "request_timeout", "The request took too long")
+                .with_retry_after(RETRY_AFTER_SECONDS)
+                .into_response()
+        }
+    }
+}
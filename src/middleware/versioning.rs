@@ -0,0 +1,34 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue, header::LINK},
+    middleware::Next,
+    response::Response,
+};
+
+/// Prefix of the version the unversioned routes are aliases of.
+pub const ALIASED_VERSION: &str = "/v1";
+
+pub static DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+pub static SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+// The unversioned routes were deprecated on 2026-10-19 (RFC 9745 structured date)
+const UNVERSIONED_DEPRECATED_AT: &str = "@1792368000";
+// and are removed after this date (RFC 8594 HTTP-date)
+const UNVERSIONED_SUNSET: &str = "Fri, 30 Apr 2027 00:00:00 GMT";
+
+/// Marks responses from the unversioned aliases as deprecated and points clients at
+/// the same route under [`ALIASED_VERSION`].
+pub async fn deprecated_alias(request: Request, next: Next) -> Response {
+    let successor = format!("<{ALIASED_VERSION}{}>; rel=\"successor-version\"", request.uri().path());
+
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION.clone(), HeaderValue::from_static(UNVERSIONED_DEPRECATED_AT));
+    headers.insert(SUNSET.clone(), HeaderValue::from_static(UNVERSIONED_SUNSET));
+    if let Ok(value) = HeaderValue::from_str(&successor) {
+        headers.append(LINK, value);
+    }
+
+    response
+}
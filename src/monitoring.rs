@@ -0,0 +1,62 @@
+use std::{sync::OnceLock, time::{Duration, Instant}};
+
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+use crate::{services::database::database::pool, shutdown};
+
+/// Seconds; covers fast database-backed routes up to slow LLM calls.
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// Questions per generation request; the routes cap a request at 50.
+const BATCH_SIZE_BUCKETS: &[f64] = &[1.0, 2.0, 5.0, 10.0, 20.0, 50.0];
+
+/// How often histogram samples are folded into their buckets.
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Installs the global Prometheus recorder that `/metrics` renders, with a background worker
+/// keeping it up to date.
+pub fn init() -> Result<(), String> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), LATENCY_BUCKETS)
+        .and_then(|builder| builder.set_buckets_for_metric(Matcher::Full("question_batch_size".to_string()), BATCH_SIZE_BUCKETS))
+        .and_then(PrometheusBuilder::install_recorder)
+        .map_err(|e| format!("Failed to install metrics recorder: {e}"))?;
+
+    let upkeep_handle = handle.clone();
+    shutdown::spawn_worker("metrics_upkeep", async move {
+        let mut interval = tokio::time::interval(UPKEEP_INTERVAL);
+        loop {
+            tokio::select! {
+                () = shutdown::requested() => break,
+                _ = interval.tick() => upkeep_handle.run_upkeep(),
+            }
+        }
+    });
+
+    HANDLE.set(handle).map_err(|_| "Metrics recorder is already installed".to_string())
+}
+
+/// The current metrics in the Prometheus text format, or `None` when [`init`] was not called.
+pub fn render() -> Option<String> {
+    let handle = HANDLE.get()?;
+
+    let status = pool().status();
+    gauge!("db_pool_max_connections").set(status.max_size as f64);
+    gauge!("db_pool_connections", "state" => "idle").set(status.available as f64);
+    gauge!("db_pool_connections", "state" => "in_use").set(status.size.saturating_sub(status.available) as f64);
+    gauge!("db_pool_waiting").set(status.waiting as f64);
+
+    Some(handle.render())
+}
+
+/// Records how long a call to the generator or OpenAI took, and counts it when it failed.
+pub fn record_dependency_call<T, E>(dependency: &'static str, operation: &'static str, started: Instant, result: &Result<T, E>) {
+    histogram!("dependency_request_duration_seconds", "dependency" => dependency, "operation" => operation)
+        .record(started.elapsed().as_secs_f64());
+    if result.is_err() {
+        counter!("dependency_errors_total", "dependency" => dependency, "operation" => operation).increment(1);
+    }
+}
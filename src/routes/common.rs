@@ -0,0 +1,166 @@
+use std::{collections::{BTreeMap, BTreeSet}, sync::Arc};
+
+use axum::{Json, Router, extract::State, http::StatusCode, middleware::{from_fn, from_fn_with_state}, response::IntoResponse, routing::get};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        self,
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+use tower_http::limit::RequestBodyLimitLayer;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    enums::{dependency::Dependency, health_status::HealthStatus},
+    config::Config,
+    middleware::{
+        body_limit::payload_too_large,
+        http_metrics::record_metrics,
+        request_id::request_id,
+        security::{cors_layer, security_headers},
+        timeout::request_timeout,
+        versioning::{ALIASED_VERSION, deprecated_alias},
+    },
+    monitoring,
+    services::health::readiness,
+    state::AppState,
+    structs::health::HealthReport,
+};
+
+/// Registers the `bearer_auth` scheme the `security(...)` of every protected path refers to.
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut openapi::OpenApi) {
+        openapi.components.get_or_insert_with(Default::default).add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build()
+            ),
+        );
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(pong, metrics, live, ready), components(schemas(HealthReport)), tags())]
+struct BaseApi;
+
+#[utoipa::path(
+    get,
+    path = "/ping",
+    responses(
+        (status = 200, description = "Life check")
+    )
+)]
+async fn pong() -> &'static str {
+    "pong"
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Prometheus metrics", body = String, content_type = "text/plain"),
+        (status = 503, description = "Metrics are not enabled in this binary")
+    )
+)]
+async fn metrics() -> impl IntoResponse {
+    match monitoring::render() {
+        Some(body) => (StatusCode::OK, body),
+        None => (StatusCode::SERVICE_UNAVAILABLE, "Metrics are not enabled".to_string()),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    responses(
+        (status = 200, description = "The process is running", body = HealthReport)
+    )
+)]
+async fn live() -> Json<HealthReport> {
+    Json(HealthReport { status: HealthStatus::Up, checks: BTreeMap::new() })
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    responses(
+        (status = 200, description = "Every dependency is up", body = HealthReport),
+        (status = 503, description = "At least one dependency is down", body = HealthReport)
+    )
+)]
+async fn ready(config: Arc<Config>, dependencies: Arc<[Dependency]>) -> impl IntoResponse {
+    let report = readiness(&dependencies, &config).await;
+    let status = match report.status {
+        HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    };
+    (status, Json(report))
+}
+
+/// Mounts `routes` under `/v1`, and again at the root as deprecated aliases.
+pub fn versioned(routes: Router<AppState>) -> Router<AppState> {
+    Router::new()
+        .nest(ALIASED_VERSION, routes.clone())
+        .merge(routes.layer(from_fn(deprecated_alias)))
+}
+
+/// Documents one version of a service's routes under `/{version}`. Operation ids get the
+/// version as a prefix so handlers shared between versions stay unique in one document.
+pub fn nest_version(doc: openapi::OpenApi, version: &str, mut api: openapi::OpenApi) -> openapi::OpenApi {
+    for item in api.paths.paths.values_mut() {
+        let operations = [&mut item.get, &mut item.put, &mut item.post, &mut item.delete, &mut item.patch];
+        for operation in operations.into_iter().flatten() {
+            if let Some(operation_id) = operation.operation_id.as_mut() {
+                *operation_id = format!("{version}_{operation_id}");
+            }
+        }
+    }
+    doc.nest(format!("/{version}"), api)
+}
+
+/// Combines the documents of every service mounted in one binary.
+pub fn api_doc(services: impl IntoIterator<Item = openapi::OpenApi>) -> openapi::OpenApi {
+    let mut doc = BaseApi::openapi();
+    for service in services {
+        doc.merge(service);
+    }
+    SecurityAddon.modify(&mut doc);
+    doc
+}
+
+/// Wraps the routes of one or more services with what every binary serves: the docs,
+/// `/ping`, `/metrics`, health checks of the services' `dependencies`, and the layers every
+/// request passes through: request ids, CORS, security headers, metrics, the body limit and the timeout.
+pub fn app(
+    routes: Router<AppState>,
+    doc: openapi::OpenApi,
+    dependencies: impl IntoIterator<Item = &'static Dependency>,
+    state: AppState,
+) -> Router {
+    let dependencies: Arc<[Dependency]> = dependencies.into_iter().copied().collect::<BTreeSet<_>>().into_iter().collect();
+    let server = &state.config.server;
+    let cors = cors_layer(&state.config.cors).expect("CORS settings are checked when the config loads");
+
+    let mut app = Router::new()
+        .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", doc))
+        .route("/ping", get(pong))
+        .route("/metrics", get(metrics))
+        .route("/health/live", get(live))
+        .route("/health/ready", get(move |State(config): State<Arc<Config>>| ready(config, dependencies.clone())))
+        .merge(routes)
+        .layer(from_fn_with_state(state.clone(), request_timeout))
+        .layer(RequestBodyLimitLayer::new(server.body_limit))
+        .layer(from_fn_with_state(state.clone(), payload_too_large))
+        .layer(from_fn(record_metrics))
+        .layer(from_fn_with_state(state.clone(), security_headers));
+    if let Some(cors) = cors {
+        app = app.layer(cors);
+    }
+    app.layer(from_fn(request_id)).with_state(state)
+}
@@ -0,0 +1,975 @@
+use axum::{
+    Json, Router, extract::State, http::{StatusCode, header::CONTENT_DISPOSITION}, response::IntoResponse, routing::{delete, get, patch, post}
+};
+use metrics::counter;
+use serde::Deserialize;
+use std::sync::Arc;
+use base64::Engine;
+use utoipa::OpenApi;
+use crate::{
+    config::Config,
+    enums::dependency::Dependency,
+    error::{ApiError, ErrorBody},
+    helpers::password_policy::PasswordPolicy,
+    middleware::{
+        auth::{Admin, AuthenticatedUser, AuthorizedUser, StaffClient, Teacher},
+        client_info::ClientInfo,
+        extract::{ApiJson, ApiPath, ApiQuery},
+        rate_limit::{AnswerRoutes, AuthRoutes, RateLimited},
+    },
+    routes::common::{nest_version, versioned},
+    services::database::{
+        account::{
+            change_password,
+            check_password,
+            check_token,
+            create_account,
+            fetch_details,
+            issue_reset_token,
+            list_sessions,
+            redeem_reset_token,
+            revoke_session,
+            set_role
+        },
+        jwt::issue_access_token,
+        login_attempts::list_lockout_events,
+        knowledge_service::{
+            get_all_progression_score,
+            get_knowledge_score,
+            get_skill,
+            update_knowledge_score,
+            log_progress,
+            get_historical_skills,
+            get_skill_history // ✅ added
+        },
+        assignment_service::{
+            create_assignment,
+            get_assignment_status,
+            get_open_assignments,
+            list_class_assignments,
+            record_assignment_answer
+        },
+        class_service::{
+            create_class,
+            get_class_roster,
+            get_class_skill_averages,
+            get_managed_class,
+            get_students_below_threshold,
+            join_class,
+            list_classes
+        },
+        personal_data::{delete_account, export_user_data},
+        signing_keys::local_jwks
+    },
+    structs::{
+        account::Account,
+        assignment::{Assignment, AssignmentCreate, AssignmentStatus, StudentAssignment},
+        class::{Class, ClassCreate, ClassJoin, ClassStudent, SkillAverage},
+        data_export::{AccountDeletion, DataExport},
+        lockout_event::LockoutEvent,
+        login::LoginResponse,
+        password::{PasswordChange, PasswordReset, ResetToken},
+        knowledge_score_request::KnowledgeScoreRequest,
+        knowledge_score_update::KnowledgeScoreUpdate,
+        performance_update::PerformanceUpdate,
+        role_update::RoleUpdate,
+        session::Session,
+        sign_in::SignIn,
+        skill::Skill,
+        skill_progression::{SkillProgression, SkillProgressionWithDate},
+        token_validation::TokenValidation
+    },
+    state::AppState
+};
+use als_algorithm::models::knowledge_tracing_model::calculate_mastery;
+
+const DEFAULT_MASTERY_THRESHOLD: f64 = 0.5;
+const LOCKOUT_EVENT_LIMIT: i64 = 100;
+
+#[derive(Deserialize)]
+struct ThresholdQuery {
+    threshold: Option<f64>,
+}
+
+/// Paths served identically by every version.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        skill_update,
+        register_account,
+        validate_token,
+        fetch_user_details,
+        get_progression,
+        log_progress_endpoint,
+        get_historical_skills_endpoint,
+        get_skill_history_endpoint,
+        get_sessions,
+        delete_session,
+        update_user_role,
+        create_class_endpoint,
+        list_classes_endpoint,
+        join_class_endpoint,
+        get_class_roster_endpoint,
+        get_class_averages_endpoint,
+        get_class_below_threshold_endpoint,
+        create_assignment_endpoint,
+        list_class_assignments_endpoint,
+        get_assignment_status_endpoint,
+        get_open_assignments_endpoint,
+        get_lockout_events,
+        change_password_endpoint,
+        issue_reset_token_endpoint,
+        redeem_reset_token_endpoint,
+        export_account_data,
+        delete_account_endpoint
+    ),
+    components(schemas(ErrorBody))
+)]
+struct SharedApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(login))]
+struct V1Api;
+
+#[derive(OpenApi)]
+#[openapi(paths(login_v2))]
+struct V2Api;
+
+#[derive(OpenApi)]
+#[openapi(paths(jwks))]
+struct KtApi;
+
+/// What the knowledge tracing routes need to serve requests.
+pub const DEPENDENCIES: &[Dependency] = &[Dependency::Database];
+
+/// OpenAPI document for the knowledge tracing routes.
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let mut v1 = V1Api::openapi();
+    v1.merge(SharedApi::openapi());
+    let mut v2 = V2Api::openapi();
+    v2.merge(SharedApi::openapi());
+
+    let doc = KtApi::openapi().nest("/v1", v1);
+    nest_version(doc, "v2", v2)
+}
+
+/// Knowledge tracing routes: accounts, skills, classes and the JWKS.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/.well-known/jwks.json", get(jwks))
+        .merge(versioned(v1_routes()))
+        .nest("/v2", v2_routes())
+}
+
+/// Routes served under `/v1`, and at the root as deprecated aliases. Each group is its own
+/// function so later versions can reuse the groups they keep and replace the rest.
+pub fn v1_routes() -> Router<AppState> {
+    Router::new()
+        .route("/accounts/login", post(login))
+        .merge(skill_routes())
+        .merge(account_routes())
+        .merge(admin_routes())
+        .merge(class_routes())
+}
+
+/// Routes served under `/v2`, where login returns a JSON token response.
+pub fn v2_routes() -> Router<AppState> {
+    Router::new()
+        .route("/accounts/login", post(login_v2))
+        .merge(skill_routes())
+        .merge(account_routes())
+        .merge(admin_routes())
+        .merge(class_routes())
+}
+
+fn skill_routes() -> Router<AppState> {
+    Router::new()
+        .route("/students/skills/", get(get_progression))
+        .route("/students/skills/{skill}/performance", patch(skill_update))
+        .route("/students/skills/{skill}/log", post(log_progress_endpoint))
+        .route("/students/skills/history", get(get_historical_skills_endpoint))
+        .route("/students/skills/{skill}/history", get(get_skill_history_endpoint))
+}
+
+fn account_routes() -> Router<AppState> {
+    Router::new()
+        .route("/accounts/register", post(register_account))
+        .route("/accounts/validate", post(validate_token))
+        .route("/accounts/fetch", get(fetch_user_details))
+        .route("/accounts/export", get(export_account_data))
+        .route("/accounts", delete(delete_account_endpoint))
+        .route("/accounts/sessions", get(get_sessions))
+        .route("/accounts/sessions/{session_id}", delete(delete_session))
+        .route("/accounts/password", patch(change_password_endpoint))
+        .route("/accounts/password/reset", post(redeem_reset_token_endpoint))
+        .route("/accounts/{user_id}/reset_token", post(issue_reset_token_endpoint))
+}
+
+fn admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/users/{user_id}/role", patch(update_user_role))
+        .route("/admin/lockouts", get(get_lockout_events))
+}
+
+fn class_routes() -> Router<AppState> {
+    Router::new()
+        .route("/classes", post(create_class_endpoint).get(list_classes_endpoint))
+        .route("/classes/join", post(join_class_endpoint))
+        .route("/classes/{class_id}/roster", get(get_class_roster_endpoint))
+        .route("/classes/{class_id}/averages", get(get_class_averages_endpoint))
+        .route("/classes/{class_id}/below_threshold", get(get_class_below_threshold_endpoint))
+        .route("/classes/{class_id}/assignments", post(create_assignment_endpoint).get(list_class_assignments_endpoint))
+        .route("/classes/{class_id}/assignments/{assignment_id}/status", get(get_assignment_status_endpoint))
+        .route("/students/assignments", get(get_open_assignments_endpoint))
+}
+
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    responses(
+        (status = 200, description = "Public keys access tokens are signed with", body = Object),
+        (status = 503, description = "Signing keys not configured", body = ErrorBody)
+    )
+)]
+async fn jwks() -> Result<impl IntoResponse, ApiError> {
+    let jwks = local_jwks()
+        .map_err(|e| ApiError::unavailable("signing_keys_unavailable", "Signing keys are not configured", e))?;
+
+    Ok(Json(jwks))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/students/skills/{skill}/performance",
+    request_body = PerformanceUpdate,
+    params(
+        ("skill" = String, Path, description = "Skill id, slug or display name")
+    ),
+    responses(
+        (status = 200, description = "Student Knowledge Update", body = f64),
+        (status = 400, description = "Bad request", body = ErrorBody),
+        (status = 404, description = "Unknown skill or no progression for it", body = ErrorBody),
+        (status = 429, description = "Too many requests - Retry after the given delay", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn skill_update(
+    _limit: RateLimited<AnswerRoutes>,
+    auth: AuthenticatedUser,
+    ApiPath(skill): ApiPath<String>, 
+    ApiJson(body): ApiJson<PerformanceUpdate>
+) -> Result<impl IntoResponse, ApiError> {
+    let student_id = auth.claims.uid;
+
+    let skill_id = get_skill(&skill).await?.skill_id;
+
+    let fetch_skill = KnowledgeScoreRequest { skill_id, student_id };
+
+    let existing_knowledge_score = get_knowledge_score(fetch_skill).await?;
+
+    let new_knowledge_score = calculate_mastery(
+        existing_knowledge_score,
+        0.05,
+        0.3,
+        0.4,
+        body.correct
+    ).await;
+
+    let knowledge_update = KnowledgeScoreUpdate {
+        skill_id,
+        student_id,
+        score: new_knowledge_score
+    };
+
+    update_knowledge_score(knowledge_update).await?;
+
+    record_assignment_answer(student_id, skill_id, new_knowledge_score).await?;
+
+    counter!("answers_recorded_total", "outcome" => if body.correct { "correct" } else { "incorrect" }).increment(1);
+    if existing_knowledge_score < DEFAULT_MASTERY_THRESHOLD && new_knowledge_score >= DEFAULT_MASTERY_THRESHOLD {
+        counter!("mastery_crossings_total").increment(1);
+    }
+
+    Ok(Json(new_knowledge_score))
+}
+
+#[utoipa::path(
+    post,
+    path = "/students/skills/{skill}/log",
+    params(
+        ("skill" = String, Path, description = "Skill id, slug or display name to log progression for")
+    ),
+    responses(
+        (status = 200, description = "Progress logged successfully"),
+        (status = 400, description = "Failed to log progression", body = ErrorBody),
+        (status = 404, description = "Unknown skill or no progression for it", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn log_progress_endpoint(
+    auth: AuthenticatedUser,
+    ApiPath(skill): ApiPath<String>
+) -> Result<impl IntoResponse, ApiError> {
+    let user_id = auth.claims.uid;
+
+    log_progress(user_id, &skill).await?;
+
+    Ok((StatusCode::OK, "Progress logged successfully"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/students/skills/history",
+    responses(
+        (status = 200, description = "List of historical skills", body = Vec<Skill>),
+        (status = 400, description = "Failed to fetch historical skills", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_historical_skills_endpoint(auth: AuthenticatedUser) -> Result<impl IntoResponse, ApiError> {
+    let user_id = auth.claims.uid;
+
+    let skills = get_historical_skills(user_id).await?;
+
+    Ok(Json(skills))
+}
+
+#[utoipa::path(
+    get,
+    path = "/students/skills/{skill}/history",
+    params(
+        ("skill" = String, Path, description = "Skill id, slug or display name to fetch history for")
+    ),
+    responses(
+        (status = 200, description = "Skill history", body = Vec<SkillProgressionWithDate>),
+        (status = 400, description = "Failed to fetch history", body = ErrorBody),
+        (status = 404, description = "Unknown skill", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_skill_history_endpoint(
+    auth: AuthenticatedUser,
+    ApiPath(skill): ApiPath<String>
+) -> Result<impl IntoResponse, ApiError> {
+    let user_id = auth.claims.uid;
+
+    let history = get_skill_history(user_id, &skill).await?;
+
+    Ok(Json(history))
+}
+
+#[utoipa::path(
+    post,
+    path = "/accounts/register",
+    request_body = Account,
+    responses(
+        (status = 201, description = "Account created successfully"),
+        (status = 400, description = "Bad request - Invalid account details", body = ErrorBody),
+        (status = 403, description = "Forbidden - Registration is disabled", body = ErrorBody),
+        (status = 409, description = "Conflict - Username already taken", body = ErrorBody),
+        (status = 500, description = "Account creation failed", body = ErrorBody),
+        (status = 429, description = "Too many requests - Retry after the given delay", body = ErrorBody)
+    )
+)]
+async fn register_account(
+    _limit: RateLimited<AuthRoutes>,
+    State(config): State<Arc<Config>>,
+    State(password_policy): State<Arc<PasswordPolicy>>,
+    ApiJson(account): ApiJson<Account>
+) -> Result<impl IntoResponse, ApiError> {
+    if !config.features.registration {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "registration_disabled", "Registration is disabled"));
+    }
+
+    create_account(account, &password_policy).await?;
+
+    Ok((StatusCode::CREATED, "Account created successfully"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/students/skills/",
+    responses(
+        (status = 200, description = "Json of skill progression", body = Vec<SkillProgression>),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_progression(auth: AuthenticatedUser) -> Result<impl IntoResponse, ApiError> {
+    let user_id = auth.claims.uid;
+
+    let progression = get_all_progression_score(user_id).await?;
+
+    Ok(Json(progression))
+}
+
+#[utoipa::path(
+    post,
+    path = "/accounts/login",
+    request_body = SignIn,
+    responses(
+        (status = 200, description = "Login successful", body = String),
+        (status = 401, description = "Unauthorized - Invalid credentials", body = ErrorBody),
+        (status = 429, description = "Too many failed attempts or requests - Retry after the given delay", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    )
+)]
+async fn login(
+    _limit: RateLimited<AuthRoutes>,
+    State(config): State<Arc<Config>>,
+    client_info: ClientInfo,
+    ApiJson(credentials): ApiJson<SignIn>
+) -> Result<impl IntoResponse, ApiError> {
+    let session = check_password(credentials, &client_info, &config.jwt).await?;
+    let token = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(session.refresh_token);
+
+    Ok((StatusCode::OK, token))
+}
+
+#[utoipa::path(
+    post,
+    path = "/accounts/login",
+    operation_id = "login",
+    request_body = SignIn,
+    responses(
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 401, description = "Unauthorized - Invalid credentials", body = ErrorBody),
+        (status = 429, description = "Too many failed attempts or requests - Retry after the given delay", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    )
+)]
+async fn login_v2(
+    _limit: RateLimited<AuthRoutes>,
+    State(config): State<Arc<Config>>,
+    client_info: ClientInfo,
+    ApiJson(credentials): ApiJson<SignIn>
+) -> Result<impl IntoResponse, ApiError> {
+    let session = check_password(credentials, &client_info, &config.jwt).await?;
+    let owner = &session.owner;
+    let access_token = issue_access_token(owner.user_id, owner.session_id, owner.role, owner.audience.as_deref(), &config.jwt)?;
+
+    Ok(Json(LoginResponse {
+        access_token: access_token.token,
+        refresh_token: base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(session.refresh_token),
+        token_type: "Bearer",
+        expires_in: config.jwt.access_token_lifetime,
+        expires_at: access_token.expires_at.to_rfc3339(),
+        user: session.user,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/accounts/validate",
+    request_body = TokenValidation,
+    responses(
+        (status = 200, description = "Token valid", body = String),
+        (status = 401, description = "Unauthorized - Invalid or expired token", body = ErrorBody),
+        (status = 400, description = "Bad request - Invalid token format", body = ErrorBody),
+        (status = 429, description = "Too many requests - Retry after the given delay", body = ErrorBody)
+    )
+)]
+async fn validate_token(
+    _limit: RateLimited<AuthRoutes>,
+    State(config): State<Arc<Config>>,
+    client_info: ClientInfo,
+    ApiJson(token_data): ApiJson<TokenValidation>
+) -> Result<impl IntoResponse, ApiError> {
+    let token_bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(&token_data.token)
+        .map_err(|_| ApiError::bad_request("Invalid token format"))?;
+
+    let token_array: [u8; 32] = token_bytes.try_into()
+        .map_err(|_| ApiError::bad_request("Invalid token length"))?;
+
+    let owner = check_token(token_array, &client_info).await?;
+    let token = issue_access_token(owner.user_id, owner.session_id, owner.role, owner.audience.as_deref(), &config.jwt)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "valid": true,
+        "user_id": owner.user_id.to_string(),
+        "jwt_token": token.token
+    }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/accounts/fetch",
+    responses(
+        (status = 200, description = "Account", body = Account),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn fetch_user_details(auth: AuthenticatedUser) -> Result<impl IntoResponse, ApiError> {
+    let account = fetch_details(&auth.claims).await?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "first_name" : account.first_name,
+        "last_name" : account.last_name,
+        "username" : account.username,
+        "role" : auth.claims.role,
+    }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/accounts/sessions",
+    responses(
+        (status = 200, description = "Active sessions for the account", body = Vec<Session>),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_sessions(auth: AuthenticatedUser) -> Result<impl IntoResponse, ApiError> {
+    let sessions = list_sessions(&auth.claims).await?;
+
+    Ok(Json(sessions))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/accounts/sessions/{session_id}",
+    params(
+        ("session_id" = i32, Path, description = "Id of the session to revoke")
+    ),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 404, description = "Session not found", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn delete_session(
+    auth: AuthenticatedUser,
+    ApiPath(session_id): ApiPath<i32>
+) -> Result<impl IntoResponse, ApiError> {
+    revoke_session(auth.claims.uid, session_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    patch,
+    path = "/admin/users/{user_id}/role",
+    request_body = RoleUpdate,
+    params(
+        ("user_id" = i32, Path, description = "Id of the user to update")
+    ),
+    responses(
+        (status = 204, description = "Role updated"),
+        (status = 403, description = "Forbidden - Requires admin role", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn update_user_role(
+    _auth: AuthorizedUser<Admin, StaffClient>,
+    ApiPath(user_id): ApiPath<i32>,
+    ApiJson(body): ApiJson<RoleUpdate>
+) -> Result<impl IntoResponse, ApiError> {
+    set_role(user_id, body.role).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/classes",
+    request_body = ClassCreate,
+    responses(
+        (status = 201, description = "Class created", body = Class),
+        (status = 403, description = "Forbidden - Requires teacher role", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn create_class_endpoint(
+    auth: AuthorizedUser<Teacher, StaffClient>,
+    ApiJson(body): ApiJson<ClassCreate>
+) -> Result<impl IntoResponse, ApiError> {
+    let class = create_class(auth.claims.uid, &body.class_name).await?;
+
+    Ok((StatusCode::CREATED, Json(class)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/classes",
+    responses(
+        (status = 200, description = "Classes taught by the caller", body = Vec<Class>),
+        (status = 403, description = "Forbidden - Requires teacher role", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn list_classes_endpoint(auth: AuthorizedUser<Teacher, StaffClient>) -> Result<impl IntoResponse, ApiError> {
+    let classes = list_classes(auth.claims.uid).await?;
+
+    Ok(Json(classes))
+}
+
+#[utoipa::path(
+    post,
+    path = "/classes/join",
+    request_body = ClassJoin,
+    responses(
+        (status = 200, description = "Joined class", body = Class),
+        (status = 404, description = "Unknown join code", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn join_class_endpoint(
+    auth: AuthenticatedUser,
+    ApiJson(body): ApiJson<ClassJoin>
+) -> Result<impl IntoResponse, ApiError> {
+    let class = join_class(auth.claims.uid, &body.join_code).await?;
+
+    Ok(Json(class))
+}
+
+#[utoipa::path(
+    get,
+    path = "/classes/{class_id}/roster",
+    params(
+        ("class_id" = i32, Path, description = "Id of the class")
+    ),
+    responses(
+        (status = 200, description = "Students with their progression per skill", body = Vec<ClassStudent>),
+        (status = 403, description = "Forbidden - Requires teacher role", body = ErrorBody),
+        (status = 404, description = "Class not found", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_class_roster_endpoint(
+    auth: AuthorizedUser<Teacher, StaffClient>,
+    ApiPath(class_id): ApiPath<i32>
+) -> Result<impl IntoResponse, ApiError> {
+    get_managed_class(class_id, &auth.claims).await?;
+
+    let roster = get_class_roster(class_id).await?;
+
+    Ok(Json(roster))
+}
+
+#[utoipa::path(
+    get,
+    path = "/classes/{class_id}/averages",
+    params(
+        ("class_id" = i32, Path, description = "Id of the class")
+    ),
+    responses(
+        (status = 200, description = "Average progression per skill across the class", body = Vec<SkillAverage>),
+        (status = 403, description = "Forbidden - Requires teacher role", body = ErrorBody),
+        (status = 404, description = "Class not found", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_class_averages_endpoint(
+    auth: AuthorizedUser<Teacher, StaffClient>,
+    ApiPath(class_id): ApiPath<i32>
+) -> Result<impl IntoResponse, ApiError> {
+    get_managed_class(class_id, &auth.claims).await?;
+
+    let averages = get_class_skill_averages(class_id).await?;
+
+    Ok(Json(averages))
+}
+
+#[utoipa::path(
+    get,
+    path = "/classes/{class_id}/below_threshold",
+    params(
+        ("class_id" = i32, Path, description = "Id of the class"),
+        ("threshold" = Option<f64>, Query, description = "Mastery threshold, defaults to 0.5")
+    ),
+    responses(
+        (status = 200, description = "Students with the skills they are below the threshold on", body = Vec<ClassStudent>),
+        (status = 403, description = "Forbidden - Requires teacher role", body = ErrorBody),
+        (status = 404, description = "Class not found", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_class_below_threshold_endpoint(
+    auth: AuthorizedUser<Teacher, StaffClient>,
+    ApiPath(class_id): ApiPath<i32>,
+    ApiQuery(query): ApiQuery<ThresholdQuery>
+) -> Result<impl IntoResponse, ApiError> {
+    get_managed_class(class_id, &auth.claims).await?;
+
+    let threshold = query.threshold.unwrap_or(DEFAULT_MASTERY_THRESHOLD);
+
+    let students = get_students_below_threshold(class_id, threshold).await?;
+
+    Ok(Json(students))
+}
+
+#[utoipa::path(
+    post,
+    path = "/classes/{class_id}/assignments",
+    request_body = AssignmentCreate,
+    params(
+        ("class_id" = i32, Path, description = "Id of the class")
+    ),
+    responses(
+        (status = 201, description = "Assignment created", body = Assignment),
+        (status = 403, description = "Forbidden - Requires teacher role", body = ErrorBody),
+        (status = 404, description = "Class not found", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn create_assignment_endpoint(
+    auth: AuthorizedUser<Teacher, StaffClient>,
+    ApiPath(class_id): ApiPath<i32>,
+    ApiJson(body): ApiJson<AssignmentCreate>
+) -> Result<impl IntoResponse, ApiError> {
+    get_managed_class(class_id, &auth.claims).await?;
+
+    let assignment = create_assignment(class_id, body).await?;
+
+    Ok((StatusCode::CREATED, Json(assignment)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/classes/{class_id}/assignments",
+    params(
+        ("class_id" = i32, Path, description = "Id of the class")
+    ),
+    responses(
+        (status = 200, description = "Assignments set for the class", body = Vec<Assignment>),
+        (status = 403, description = "Forbidden - Requires teacher role", body = ErrorBody),
+        (status = 404, description = "Class not found", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn list_class_assignments_endpoint(
+    auth: AuthorizedUser<Teacher, StaffClient>,
+    ApiPath(class_id): ApiPath<i32>
+) -> Result<impl IntoResponse, ApiError> {
+    get_managed_class(class_id, &auth.claims).await?;
+
+    let assignments = list_class_assignments(class_id).await?;
+
+    Ok(Json(assignments))
+}
+
+#[utoipa::path(
+    get,
+    path = "/classes/{class_id}/assignments/{assignment_id}/status",
+    params(
+        ("class_id" = i32, Path, description = "Id of the class"),
+        ("assignment_id" = i32, Path, description = "Id of the assignment")
+    ),
+    responses(
+        (status = 200, description = "Completion status for each student", body = AssignmentStatus),
+        (status = 403, description = "Forbidden - Requires teacher role", body = ErrorBody),
+        (status = 404, description = "Class or assignment not found", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_assignment_status_endpoint(
+    auth: AuthorizedUser<Teacher, StaffClient>,
+    ApiPath((class_id, assignment_id)): ApiPath<(i32, i32)>
+) -> Result<impl IntoResponse, ApiError> {
+    get_managed_class(class_id, &auth.claims).await?;
+
+    let status = get_assignment_status(class_id, assignment_id).await?;
+
+    Ok(Json(status))
+}
+
+#[utoipa::path(
+    get,
+    path = "/students/assignments",
+    responses(
+        (status = 200, description = "Assignments that are not yet due, with progress", body = Vec<StudentAssignment>),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_open_assignments_endpoint(auth: AuthenticatedUser) -> Result<impl IntoResponse, ApiError> {
+    let assignments = get_open_assignments(auth.claims.uid).await?;
+
+    Ok(Json(assignments))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/lockouts",
+    responses(
+        (status = 200, description = "Most recent login lockouts", body = Vec<LockoutEvent>),
+        (status = 403, description = "Forbidden - Requires admin role", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn get_lockout_events(_auth: AuthorizedUser<Admin, StaffClient>) -> Result<impl IntoResponse, ApiError> {
+    let events = list_lockout_events(LOCKOUT_EVENT_LIMIT).await?;
+
+    Ok(Json(events))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/accounts/password",
+    request_body = PasswordChange,
+    responses(
+        (status = 204, description = "Password changed and all sessions signed out"),
+        (status = 401, description = "Unauthorized - Current password is incorrect", body = ErrorBody),
+        (status = 400, description = "Bad request - New password rejected", body = ErrorBody),
+        (status = 429, description = "Too many requests - Retry after the given delay", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn change_password_endpoint(
+    _limit: RateLimited<AuthRoutes>,
+    auth: AuthenticatedUser,
+    State(password_policy): State<Arc<PasswordPolicy>>,
+    ApiJson(body): ApiJson<PasswordChange>
+) -> Result<impl IntoResponse, ApiError> {
+    change_password(auth.claims.uid, body, &password_policy).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/accounts/{user_id}/reset_token",
+    params(
+        ("user_id" = i32, Path, description = "Id of the user whose password is being reset")
+    ),
+    responses(
+        (status = 201, description = "One-time reset token for the user", body = ResetToken),
+        (status = 403, description = "Forbidden - Requires teacher role", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn issue_reset_token_endpoint(
+    auth: AuthorizedUser<Teacher, StaffClient>,
+    ApiPath(user_id): ApiPath<i32>
+) -> Result<impl IntoResponse, ApiError> {
+    let token = issue_reset_token(&auth.claims, user_id).await?;
+
+    Ok((StatusCode::CREATED, Json(token)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/accounts/password/reset",
+    request_body = PasswordReset,
+    responses(
+        (status = 204, description = "Password reset and all sessions signed out"),
+        (status = 401, description = "Unauthorized - Invalid or expired reset token", body = ErrorBody),
+        (status = 400, description = "Bad request - New password rejected", body = ErrorBody),
+        (status = 429, description = "Too many requests - Retry after the given delay", body = ErrorBody)
+    )
+)]
+async fn redeem_reset_token_endpoint(
+    _limit: RateLimited<AuthRoutes>,
+    State(password_policy): State<Arc<PasswordPolicy>>,
+    ApiJson(body): ApiJson<PasswordReset>
+) -> Result<impl IntoResponse, ApiError> {
+    redeem_reset_token(body, &password_policy).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/accounts/export",
+    responses(
+        (status = 200, description = "All data held about the account", body = DataExport),
+        (status = 400, description = "Bad request", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn export_account_data(auth: AuthenticatedUser) -> Result<impl IntoResponse, ApiError> {
+    let export = export_user_data(&auth.claims).await?;
+
+    Ok((
+        StatusCode::OK,
+        [(CONTENT_DISPOSITION, format!("attachment; filename=\"account-{}.json\"", auth.claims.uid))],
+        Json(export)
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/accounts",
+    request_body = AccountDeletion,
+    responses(
+        (status = 204, description = "Account and all associated data deleted"),
+        (status = 401, description = "Unauthorized - Password is incorrect", body = ErrorBody),
+        (status = 400, description = "Bad request", body = ErrorBody),
+        (status = 429, description = "Too many requests - Retry after the given delay", body = ErrorBody)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn delete_account_endpoint(
+    _limit: RateLimited<AuthRoutes>,
+    auth: AuthenticatedUser,
+    client_info: ClientInfo,
+    ApiJson(body): ApiJson<AccountDeletion>
+) -> Result<impl IntoResponse, ApiError> {
+    delete_account(auth.claims.uid, &body.password, &client_info).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,5 @@
+pub mod common;
+#[cfg(feature = "kt")]
+pub mod kt;
+#[cfg(feature = "question")]
+pub mod question;
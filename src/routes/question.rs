@@ -0,0 +1,219 @@
+use crate::{
+    config::Config,
+    enums::{dependency::Dependency, difficulty::Difficulty},
+    error::{ApiError, ErrorBody},
+    middleware::{
+        auth::{Admin, AuthenticatedUser, AuthorizedUser, StaffClient},
+        extract::{ApiPath, ApiQuery},
+        rate_limit::{GenerationRoutes, RateLimited, WordGenerationRoutes},
+    },
+    routes::common::versioned,
+    services::{
+        database::{
+            knowledge_service::{get_knowledge_score, get_skill},
+            question_service::{list_skills, reserve_llm_budget},
+        },
+        generator::modules::{
+            fetch_module_list,
+            generate_questions,
+            generate_word_questions,
+        },
+    },
+    structs::{
+        knowledge_score_request::KnowledgeScoreRequest,
+        question_pair::QuestionPair,
+        skill::Skill,
+    },
+    state::AppState,
+};
+
+use metrics::{counter, histogram};
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+
+use reqwest::StatusCode;
+
+use utoipa::OpenApi;
+
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+struct GenerateQuery {
+    amount: Option<usize>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_internal_modules,
+        get_modules,
+        generate,
+        generate_word
+    ),
+    components(schemas(QuestionPair, Skill, ErrorBody))
+)]
+struct V1Api;
+
+#[derive(OpenApi)]
+#[openapi(nest((path = "/v1", api = V1Api)))]
+struct QuestionApi;
+
+/// What the question routes need to serve requests.
+pub const DEPENDENCIES: &[Dependency] = &[Dependency::Database, Dependency::Generator, Dependency::Llm];
+
+/// OpenAPI document for the question routes.
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    QuestionApi::openapi()
+}
+
+/// Question routes: the skill catalogue and question generation.
+pub fn router() -> Router<AppState> {
+    versioned(v1_routes())
+}
+
+/// Routes served under `/v1`, and at the root as deprecated aliases.
+pub fn v1_routes() -> Router<AppState> {
+    Router::new()
+        .route("/generate/{skill}", get(generate))
+        .route("/generate_word/{skill}", get(generate_word))
+        .route("/internal_modules", get(get_internal_modules))
+        .route("/modules", get(get_modules))
+}
+
+#[utoipa::path(
+    get,
+    path = "/internal_modules",
+    responses(
+        (status = 200, description = "List of internal modules", body = [String]),
+        (status = 403, description = "Forbidden - Requires admin role", body = ErrorBody),
+        (status = 503, description = "Generator service unavailable", body = ErrorBody)
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn get_internal_modules(
+    _auth: AuthorizedUser<Admin, StaffClient>,
+    State(config): State<Arc<Config>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let modules = fetch_module_list(&config.generator).await?;
+
+    Ok(Json(modules))
+}
+
+#[utoipa::path(
+    get,
+    path = "/modules",
+    responses(
+        (status = 200, description = "List of modules", body = [Skill]),
+        (status = 503, description = "Database unavailable", body = ErrorBody)
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn get_modules(
+    _auth: AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let modules = list_skills().await?;
+
+    Ok(Json(modules))
+}
+
+/// Picks a difficulty from the student's current mastery of the skill.
+async fn difficulty_for(student_id: i32, skill: &Skill) -> Result<Difficulty, ApiError> {
+    let progression = get_knowledge_score(
+        KnowledgeScoreRequest { skill_id: skill.skill_id, student_id }
+    ).await?;
+
+    Ok(match progression {
+        x if x < 0.33 => Difficulty::Easy,
+        x if x < 0.66 => Difficulty::Medium,
+        _ => Difficulty::Hard,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/generate/{skill}",
+    params(
+        ("skill" = String, Path, description = "Skill id, slug or display name"),
+        ("amount" = Option<usize>, Query, description = "Number of questions")
+    ),
+    responses(
+        (status = 200, description = "Generated questions", body = [QuestionPair]),
+        (status = 404, description = "Unknown skill or no progression for it", body = ErrorBody),
+        (status = 429, description = "Too many requests - Retry after the given delay", body = ErrorBody),
+        (status = 503, description = "Generator service unavailable", body = ErrorBody)
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn generate(
+    _limit: RateLimited<GenerationRoutes>,
+    State(config): State<Arc<Config>>,
+    auth: AuthenticatedUser,
+    ApiPath(skill): ApiPath<String>,
+    ApiQuery(query): ApiQuery<GenerateQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+
+    let amount = query.amount.unwrap_or(1).min(50);
+
+    let skill = get_skill(&skill).await?;
+    let difficulty = difficulty_for(auth.claims.uid, &skill).await?;
+
+    let questions = generate_questions(&config.generator, skill.skill_slug, difficulty, amount).await?;
+
+    counter!("questions_generated_total", "kind" => "standard").increment(questions.len() as u64);
+    histogram!("question_batch_size", "kind" => "standard").record(questions.len() as f64);
+
+    Ok(Json(questions))
+}
+
+#[utoipa::path(
+    get,
+    path = "/generate_word/{skill}",
+    params(
+        ("skill" = String, Path, description = "Skill id, slug or display name"),
+        ("amount" = Option<usize>, Query, description = "Number of questions")
+    ),
+    responses(
+        (status = 200, description = "Generated word questions", body = [QuestionPair]),
+        (status = 404, description = "Unknown skill or no progression for it", body = ErrorBody),
+        (status = 429, description = "Too many requests or daily word problem limit reached - Retry after the given delay", body = ErrorBody),
+        (status = 503, description = "Generator service unavailable or word problems disabled", body = ErrorBody)
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn generate_word(
+    _limit: RateLimited<WordGenerationRoutes>,
+    State(config): State<Arc<Config>>,
+    auth: AuthenticatedUser,
+    ApiPath(skill): ApiPath<String>,
+    ApiQuery(query): ApiQuery<GenerateQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+
+    if !config.features.word_problems {
+        return Err(ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "word_problems_disabled",
+            "Word problems are disabled",
+        ));
+    }
+
+    let amount = query.amount.unwrap_or(1).min(50);
+
+    let skill = get_skill(&skill).await?;
+    let difficulty = difficulty_for(auth.claims.uid, &skill).await?;
+
+    let reservation = reserve_llm_budget(auth.claims.uid, amount, config.llm.daily_budget).await?;
+
+    let questions = generate_word_questions(&config.generator, &config.llm, skill.skill_slug, difficulty, amount).await?;
+    reservation.keep();
+
+    counter!("questions_generated_total", "kind" => "word").increment(questions.len() as u64);
+    histogram!("question_batch_size", "kind" => "word").record(questions.len() as f64);
+
+    Ok(Json(questions))
+}
@@ -0,0 +1,59 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::Router;
+use axum_server::{Handle, tls_rustls::RustlsConfig};
+
+use crate::{config::ServerConfig, services::database::database::pool, shutdown, tls};
+
+/// Serves `app` until SIGTERM or SIGINT, over HTTPS when a certificate is configured. The
+/// listener closes straight away, in-flight requests get up to `server.shutdown_timeout`
+/// seconds to finish, then background workers stop and the database pool closes.
+pub async fn serve(app: Router, server: &ServerConfig) -> std::io::Result<()> {
+    let listener = std::net::TcpListener::bind(server.bind_address)?;
+    listener.set_nonblocking(true)?;
+
+    let handle = Handle::new();
+    let stop_accepting = handle.clone();
+    tokio::spawn(async move {
+        shutdown::signal().await;
+        stop_accepting.graceful_shutdown(None);
+    });
+
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    let serving = async {
+        match server.tls() {
+            Some((cert_file, key_file)) => {
+                let config = tls::server_config(cert_file, key_file).map_err(std::io::Error::other)?;
+                let rustls = RustlsConfig::from_config(Arc::new(config));
+                tls::reload_on_change(rustls.clone(), cert_file.to_path_buf(), key_file.to_path_buf());
+
+                tracing::info!(address = %server.bind_address, "Listening over HTTPS");
+                axum_server::from_tcp_rustls(listener, rustls).handle(handle).serve(service).await
+            }
+            None => {
+                tracing::info!(address = %server.bind_address, "Listening");
+                axum_server::from_tcp(listener).handle(handle).serve(service).await
+            }
+        }
+    };
+
+    let deadline = Duration::from_secs(server.shutdown_timeout);
+    let drain_deadline = async {
+        shutdown::requested().await;
+        tokio::time::sleep(deadline).await;
+    };
+
+    tokio::select! {
+        result = serving => result?,
+        () = drain_deadline => tracing::warn!(
+            "In-flight requests did not finish within {}s; dropping them",
+            deadline.as_secs()
+        ),
+    }
+
+    shutdown::stop_workers().await;
+    pool().close();
+    tracing::info!("Shutdown complete");
+
+    Ok(())
+}
@@ -1,5 +1,10 @@
-use crate::{services::database::database::get_connection_string, structs::{account::Account, claims::Claims, sign_in::SignIn}};
-use tokio_postgres::NoTls;
+use std::sync::OnceLock;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use crate::{config::JwtConfig, enums::role::Role, helpers::{password_policy::PasswordPolicy, username::normalise_username}, middleware::client_info::ClientInfo, services::database::{database::get_client, knowledge_service::INITIAL_PROGRESSION, login_attempts::{begin_login_attempt, finish_login_attempt}}, structs::{account::Account, claims::Claims, password::{PasswordChange, PasswordReset, ResetToken}, login::UserProfile, session::{NewSession, Session, SessionOwner}, sign_in::SignIn}};
+use deadpool_postgres::GenericClient;
+use metrics::counter;
+use tokio_postgres::error::SqlState;
 use argon2::{
     password_hash::{
         PasswordHash, PasswordHasher, PasswordVerifier, SaltString
@@ -7,11 +12,14 @@ use argon2::{
     Argon2
 };
 use rand_core::{OsRng, RngCore};
+use ring::digest;
 
 #[derive(thiserror::Error, Debug)]
 pub enum AccountError {
     #[error("Database error: {0}")]
     Database(String),
+    #[error("Database unavailable: {0}")]
+    Unavailable(String),
     #[error("Hashing error: {0}")]
     Hashing(String),
     #[error("Authentication error: {0}")]
@@ -22,11 +30,20 @@ pub enum AccountError {
     InvalidToken(String),
     #[error("Validation error: {0}")]
     Validation(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    #[error("Too many failed login attempts, retry after {0} seconds")]
+    Locked(u64),
     #[error("Unexpected error: {0}")]
     Other(#[from] anyhow::Error),
 }
 
-pub async fn create_account(new_account: Account) -> Result<(), AccountError> {
+const RESET_TOKEN_LIFETIME: Duration = Duration::hours(24);
+
+#[tracing::instrument(skip_all)]
+pub async fn create_account(new_account: Account, policy: &PasswordPolicy) -> Result<(), AccountError> {
     if new_account.first_name.trim().is_empty()
         || new_account.last_name.trim().is_empty()
         || new_account.username.trim().is_empty()
@@ -36,80 +53,115 @@ pub async fn create_account(new_account: Account) -> Result<(), AccountError> {
             "All account fields must be provided and non-empty".to_string(),
         ));
     }
-    let connection_string = get_connection_string().await
-        .map_err(|e| AccountError::Database(format!("Failed to build connection string: {e}")))?;
+    let username = normalise_username(&new_account.username)
+        .map_err(AccountError::Validation)?;
+    let hash = hash_new_password(&new_account.password, &username, policy)?;
 
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| AccountError::Database(format!("Failed to connect to DB: {e}")))?;
+    let mut client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
+    let transaction = client.transaction()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to start transaction: {e}")))?;
 
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let hash = argon2
-        .hash_password(new_account.password.as_bytes(), &salt)
-        .map_err(|e | AccountError::Hashing(format!("Failed to hash password: {e}")))?
-        .to_string();
-    
-    client.execute(
-        "INSERT INTO users (first_name, last_name, username, password_hash) VALUES($1, $2, $3, $4)", 
-        &[&new_account.first_name, &new_account.last_name, &new_account.username, &hash]
+    let user_id: i32 = transaction.query_one(
+        "INSERT INTO users (first_name, last_name, username, password_hash) VALUES($1, $2, $3, $4) RETURNING user_id", 
+        &[&new_account.first_name, &new_account.last_name, &username, &hash]
     )
         .await
-        .map_err(|e| AccountError::Database(format!("Failed to insert new user: {e}")))?;
-    let user_id: i32 = client.query_one("SELECT user_id FROM USERS WHERE username=$1", &[&new_account.username])
-        .await
-        .map_err(|e| AccountError::Database(format!("Failed to fetch user id: {e}")))?
+        .map_err(|e| match e.code() {
+            Some(code) if *code == SqlState::UNIQUE_VIOLATION => AccountError::Conflict("Username is already taken".to_string()),
+            _ => AccountError::Database(format!("Failed to insert new user: {e}")),
+        })?
         .get(0);
-    client.execute("INSERT INTO progression (user_id, skill_id, progression) SELECT $1 AS user_id, s.skill_id, 0.1 AS progression FROM skills s ON CONFLICT (user_id, skill_id) DO NOTHING;", &[&user_id])
+    transaction.execute("INSERT INTO progression (user_id, skill_id, progression) SELECT $1 AS user_id, s.skill_id, $2 AS progression FROM skills s ON CONFLICT (user_id, skill_id) DO NOTHING;", &[&user_id, &INITIAL_PROGRESSION])
         .await
         .map_err(|e| AccountError::Database(format!("Failed to add skills to database: {e}")))?;
 
+    transaction.commit()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to commit new account: {e}")))?;
+
+    counter!("accounts_registered_total").increment(1);
+
     Ok(())
 }
 
-pub async fn check_password(account_details: SignIn) -> Result<[u8; 32], AccountError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| AccountError::Database(format!("Failed to build connection string: {e}")))?;
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| AccountError::Database(format!("Failed to connect to DB: {e}")))?;
-    
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
+/// Verifies the credentials and opens a new session for the user.
+#[tracing::instrument(skip_all)]
+pub async fn check_password(account_details: SignIn, client_info: &ClientInfo, jwt: &JwtConfig) -> Result<NewSession, AccountError> {
+    let client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
     
-    let row = client.query_one(
-        "SELECT password_hash FROM users WHERE username=$1", 
-        &[&account_details.username]
+    jwt.resolve_audience(account_details.audience.as_deref()).map_err(AccountError::Validation)?;
+
+    let username = account_details.username.trim().to_lowercase();
+    let ip_address = client_info.ip_address.as_deref();
+    let attempt_id = begin_login_attempt(&username, ip_address).await?;
+
+    let row = client.query_opt(
+        "SELECT password_hash, user_id, username, first_name, last_name, role FROM users WHERE lower(username)=lower($1)",
+        &[&username]
     )
         .await
         .map_err(|e| AccountError::Database(format!("Failed to find user: {e}")))?;
     
-    let hash: String = row.get(0);
+    // Unknown usernames still pay for a full verify so timing does not reveal which accounts exist
+    let hash: String = match &row {
+        Some(row) => row.get(0),
+        None => dummy_hash()?.to_string(),
+    };
     let parsed_hash = PasswordHash::new(&hash)
         .map_err(|e| AccountError::Hashing(format!("Failed to parse stored hash: {e}")))?;
     
     let argon2 = Argon2::default();
     
+    let verified = argon2.verify_password(account_details.password.as_bytes(), &parsed_hash).is_ok();
+    finish_login_attempt(attempt_id, &username, ip_address, verified).await?;
 
-    argon2.verify_password(account_details.password.as_bytes(), &parsed_hash)
-        .map_err(|_| AccountError::Authentication("Invalid account details".to_string()))?;
-    
+    let row = match row {
+        Some(row) if verified => row,
+        _ => return Err(AccountError::Authentication("Invalid account details".to_string())),
+    };
 
-    let bytes = create_refresh_token(&account_details).await?;
-    
-    Ok(bytes)
+    let role: String = row.get(5);
+    let user = UserProfile {
+        user_id: row.get(1),
+        username: row.get(2),
+        first_name: row.get(3),
+        last_name: row.get(4),
+        role: role.parse::<Role>()
+            .map_err(|e| AccountError::Database(format!("Invalid role stored for user: {e}")))?,
+    };
+
+    let (refresh_token, session_id) = create_refresh_token(user.user_id, account_details.audience.as_deref(), client_info).await?;
+
+    Ok(NewSession {
+        refresh_token,
+        owner: SessionOwner { session_id, user_id: user.user_id, role: user.role, audience: account_details.audience },
+        user,
+    })
+}
+
+/// Hash of a random password that logins for unknown usernames are checked against.
+/// The services call it at startup so a hashing failure stops them rather than letting
+/// unknown usernames fail faster than real ones.
+pub fn dummy_hash() -> Result<&'static str, AccountError> {
+    static DUMMY_HASH: OnceLock<Result<String, String>> = OnceLock::new();
+    DUMMY_HASH.get_or_init(|| {
+        let mut password = [0u8; 32];
+        OsRng.fill_bytes(&mut password);
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(&password, &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| format!("Failed to hash dummy password: {e}"))
+    })
+        .as_deref()
+        .map_err(|e| AccountError::Hashing(e.clone()))
 }
 
-async fn create_refresh_token(account_details: &SignIn) -> Result<[u8; 32], AccountError> {
+async fn create_refresh_token(user_id: i32, audience: Option<&str>, client_info: &ClientInfo) -> Result<([u8; 32], i32), AccountError> {
     let mut bytes = [0u8; 32];
     OsRng.fill_bytes(&mut bytes);
 
@@ -121,87 +173,121 @@ async fn create_refresh_token(account_details: &SignIn) -> Result<[u8; 32], Acco
         .map_err(|e | AccountError::Hashing(format!("Failed to hash password: {e}")))?
         .to_string();
 
-    let connection_string = get_connection_string().await
-        .map_err(|e| AccountError::Database(format!("Failed to build connection string: {e}")))?;
+    let client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| AccountError::Database(format!("Failed to connect to DB: {e}")))?;
 
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
-
-
-    let row = client.query_one(
-        "SELECT user_id FROM users WHERE username=$1", 
-        &[&account_details.username]
-    )
-        .await
-        .map_err(|e | AccountError::Database(format!("Failed to find user: {e}")))?;
-    
-    let user_id: i32 = row.get(0); 
-
-    client.execute(
-        "INSERT INTO sessions (user_id, refresh_token_hash) VALUES($1, $2)", 
-        &[&user_id, &hash]
+    let session_id: i32 = client.query_one(
+        "INSERT INTO sessions (user_id, refresh_token_hash, user_agent, ip_address, audience) VALUES($1, $2, $3, $4, $5) RETURNING session_id",
+        &[&user_id, &hash, &client_info.user_agent, &client_info.ip_address, &audience]
     )
         .await
-        .map_err(|e| AccountError::Database(format!("Failed to insert session: {e}")))?;
+        .map_err(|e| AccountError::Database(format!("Failed to insert session: {e}")))?
+        .get(0);
 
-    Ok(bytes)
+    Ok((bytes, session_id))
 }
 
-pub async fn check_token(refresh_token: [u8; 32]) -> Result<String, AccountError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| AccountError::Database(format!("Failed to build connection string: {e}")))?;
+#[tracing::instrument(skip_all)]
+pub async fn check_token(refresh_token: [u8; 32], client_info: &ClientInfo) -> Result<SessionOwner, AccountError> {
+    let client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| AccountError::Database(format!("Failed to connect to DB: {e}")))?;
-
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
-
-    let rows = client.query("SELECT user_id, refresh_token_hash FROM sessions", &[])
+    let rows = client.query("SELECT s.session_id, s.user_id, s.refresh_token_hash, u.role, s.audience FROM sessions s INNER JOIN users u ON u.user_id = s.user_id", &[])
         .await
         .map_err(|e| AccountError::Database(format!("Failed to query sessions: {e}")))?;
 
     let argon2 = Argon2::default();
 
     for row in rows {
-        let user_id: i32 = row.get(0);  
-        let hash: String = row.get(1);
+        let session_id: i32 = row.get(0);
+        let user_id: i32 = row.get(1);  
+        let hash: String = row.get(2);
         
         let parsed_hash = PasswordHash::new(&hash)
             .map_err(|e| AccountError::Hashing(format!("Failed to parse stored hash: {e}")))?;
 
         if argon2.verify_password(&refresh_token, &parsed_hash).is_ok() {
-            return Ok(user_id.to_string());  
+            client.execute(
+                "UPDATE sessions SET last_used_at = now(), user_agent = COALESCE($2, user_agent), ip_address = COALESCE($3, ip_address) WHERE session_id = $1",
+                &[&session_id, &client_info.user_agent, &client_info.ip_address]
+            )
+                .await
+                .map_err(|e| AccountError::Database(format!("Failed to update session: {e}")))?;
+
+            let role: String = row.get(3);
+            let role = role.parse::<Role>()
+                .map_err(|e| AccountError::Database(format!("Invalid role stored for user: {e}")))?;
+
+            return Ok(SessionOwner { session_id, user_id, role, audience: row.get(4) });
         }
     }
 
     Err(AccountError::Authentication("Invalid or expired refresh token".to_string()))
 }
 
-pub async fn fetch_details(claims: &Claims) -> Result<Account, AccountError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| AccountError::Database(format!("Failed to build connection string: {e}")))?;
+#[tracing::instrument(skip_all)]
+pub async fn list_sessions(claims: &Claims) -> Result<Vec<Session>, AccountError> {
+    let client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
+    let rows = client
+        .query(
+            "
+            SELECT session_id, user_agent, ip_address, created_at, last_used_at
+            FROM sessions
+            WHERE user_id = $1
+            ORDER BY last_used_at DESC
+            ",
+            &[&claims.uid],
+        )
         .await
-        .map_err(|e| AccountError::Database(format!("Failed to connect to DB: {e}")))?;
+        .map_err(|e| AccountError::Database(format!("Failed to fetch sessions: {e}")))?;
 
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
+    let sessions: Vec<Session> = rows
+        .into_iter()
+        .map(|row| {
+            let session_id: i32 = row.get(0);
+            let created_at: DateTime<Utc> = row.get(3);
+            let last_used_at: DateTime<Utc> = row.get(4);
+
+            Session {
+                session_id,
+                user_agent: row.get(1),
+                ip_address: row.get(2),
+                created_at: created_at.to_rfc3339(),
+                last_used_at: last_used_at.to_rfc3339(),
+                current: claims.sid == Some(session_id),
+            }
+        })
+        .collect();
+
+    Ok(sessions)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn revoke_session(user_id: i32, session_id: i32) -> Result<(), AccountError> {
+    let client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let deleted = client.execute(
+        "DELETE FROM sessions WHERE session_id = $1 AND user_id = $2",
+        &[&session_id, &user_id]
+    )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to revoke session: {e}")))?;
+
+    if deleted == 0 {
+        return Err(AccountError::NotFound(format!("No session with id {session_id}")));
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn fetch_details(claims: &Claims) -> Result<Account, AccountError> {
+    let client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
     let row = client.query_one("SELECT first_name, last_name, username FROM USERS WHERE user_id=$1", &[&claims.uid])
         .await
         .map_err(|e| AccountError::Database(format!("Failed to find user: {e}")))?;
@@ -209,4 +295,198 @@ pub async fn fetch_details(claims: &Claims) -> Result<Account, AccountError> {
     let last_name: String = row.get(1);
     let username: String = row.get(2);
     Ok(Account { first_name: first_name, last_name: last_name, username: username, password: "none".to_string() })
-}
\ No newline at end of file
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn set_role(user_id: i32, role: Role) -> Result<(), AccountError> {
+    let client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let updated = client.execute("UPDATE users SET role = $1 WHERE user_id = $2", &[&role.to_string(), &user_id])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to update role: {e}")))?;
+
+    if updated == 0 {
+        return Err(AccountError::NotFound(format!("No user with id {user_id}")));
+    }
+
+    Ok(())
+}
+
+fn hash_new_password(password: &str, username: &str, policy: &PasswordPolicy) -> Result<String, AccountError> {
+    policy.check(password, username).map_err(AccountError::Validation)?;
+
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AccountError::Hashing(format!("Failed to hash password: {e}")))
+}
+
+async fn revoke_all_sessions(client: &impl GenericClient, user_id: i32) -> Result<(), AccountError> {
+    client.execute("DELETE FROM sessions WHERE user_id = $1", &[&user_id])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to revoke sessions: {e}")))?;
+    Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn change_password(user_id: i32, change: PasswordChange, policy: &PasswordPolicy) -> Result<(), AccountError> {
+    let mut client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let row = client.query_one("SELECT username, password_hash FROM users WHERE user_id = $1", &[&user_id])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to find user: {e}")))?;
+    let username: String = row.get(0);
+    let hash: String = row.get(1);
+
+    let parsed_hash = PasswordHash::new(&hash)
+        .map_err(|e| AccountError::Hashing(format!("Failed to parse stored hash: {e}")))?;
+    Argon2::default().verify_password(change.old_password.as_bytes(), &parsed_hash)
+        .map_err(|_| AccountError::Authentication("Current password is incorrect".to_string()))?;
+
+    let new_hash = hash_new_password(&change.new_password, &username, policy)?;
+
+    let transaction = client.transaction()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to start transaction: {e}")))?;
+
+    transaction.execute("UPDATE users SET password_hash = $1 WHERE user_id = $2", &[&new_hash, &user_id])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to update password: {e}")))?;
+    revoke_all_sessions(&transaction, user_id).await?;
+
+    transaction.commit()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to commit password change: {e}")))?;
+
+    Ok(())
+}
+
+/// Issues a one-time reset token for `user_id`. Admins can reset anyone; teachers only
+/// students in one of their classes. Users the issuer may not reset are reported as not found.
+#[tracing::instrument(skip_all)]
+pub async fn issue_reset_token(issuer: &Claims, user_id: i32) -> Result<ResetToken, AccountError> {
+    let mut client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let row = client.query_opt(
+        "
+        SELECT u.role, EXISTS (
+            SELECT 1
+            FROM class_members cm
+            INNER JOIN classes c ON c.class_id = cm.class_id
+            WHERE cm.user_id = u.user_id AND c.teacher_id = $2
+        )
+        FROM users u
+        WHERE u.user_id = $1
+        ",
+        &[&user_id, &issuer.uid]
+    )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to find user: {e}")))?;
+
+    let allowed = match row {
+        Some(row) => {
+            let role: String = row.get(0);
+            let in_issuers_class: bool = row.get(1);
+            issuer.role == Role::Admin || (role == Role::Student.to_string() && in_issuers_class)
+        },
+        None => false,
+    };
+    if !allowed {
+        return Err(AccountError::NotFound(format!("No user with id {user_id}")));
+    }
+
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let hash = reset_token_digest(&bytes);
+    let expires_at = Utc::now() + RESET_TOKEN_LIFETIME;
+
+    let transaction = client.transaction()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to start transaction: {e}")))?;
+
+    transaction.execute(
+        "UPDATE password_reset_tokens SET used_at = now() WHERE user_id = $1 AND used_at IS NULL",
+        &[&user_id]
+    )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to expire previous reset tokens: {e}")))?;
+    transaction.execute(
+        "INSERT INTO password_reset_tokens (user_id, token_hash, issued_by, expires_at) VALUES ($1, $2, $3, $4)",
+        &[&user_id, &hash, &issuer.uid, &expires_at]
+    )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to store reset token: {e}")))?;
+    revoke_all_sessions(&transaction, user_id).await?;
+
+    transaction.commit()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to commit reset token: {e}")))?;
+
+    Ok(ResetToken {
+        reset_token: base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes),
+        expires_at: expires_at.to_rfc3339(),
+    })
+}
+
+/// Reset tokens are 32 random bytes, so a plain SHA-256 digest is enough to store them and
+/// lets redemption find the one row it needs instead of checking every outstanding token.
+fn reset_token_digest(token: &[u8]) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest::digest(&digest::SHA256, token))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn redeem_reset_token(reset: PasswordReset, policy: &PasswordPolicy) -> Result<(), AccountError> {
+    let token = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(&reset.reset_token)
+        .map_err(|_| AccountError::Authentication("Invalid or expired reset token".to_string()))?;
+
+    let mut client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let row = client.query_opt(
+        "
+        SELECT t.token_id, t.user_id, u.username
+        FROM password_reset_tokens t
+        INNER JOIN users u ON u.user_id = t.user_id
+        WHERE t.token_hash = $1 AND t.used_at IS NULL AND t.expires_at > now()
+        ",
+        &[&reset_token_digest(&token)]
+    )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to query reset tokens: {e}")))?
+        .ok_or_else(|| AccountError::Authentication("Invalid or expired reset token".to_string()))?;
+
+    let token_id: i32 = row.get(0);
+    let user_id: i32 = row.get(1);
+    let username: String = row.get(2);
+    let new_hash = hash_new_password(&reset.new_password, &username, policy)?;
+
+    let transaction = client.transaction()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to start transaction: {e}")))?;
+
+    // Guard against the same token being redeemed twice concurrently
+    let claimed = transaction.execute(
+        "UPDATE password_reset_tokens SET used_at = now() WHERE token_id = $1 AND used_at IS NULL",
+        &[&token_id]
+    )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to mark reset token as used: {e}")))?;
+    if claimed == 0 {
+        return Err(AccountError::Authentication("Invalid or expired reset token".to_string()));
+    }
+
+    transaction.execute("UPDATE users SET password_hash = $1 WHERE user_id = $2", &[&new_hash, &user_id])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to update password: {e}")))?;
+    revoke_all_sessions(&transaction, user_id).await?;
+
+    transaction.commit()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to commit password reset: {e}")))?;
+
+    Ok(())
+}
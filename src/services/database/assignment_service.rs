@@ -0,0 +1,329 @@
+use chrono::{DateTime, Utc};
+use tokio_postgres::Row;
+
+use crate::{
+    services::database::{database::get_client, knowledge_service::row_to_skill},
+    structs::{
+        assignment::{
+            Assignment, AssignmentCreate, AssignmentSkillProgress, AssignmentStatus, AssignmentStudentStatus, StudentAssignment
+        },
+        skill::Skill,
+    },
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum AssignmentError {
+    #[error("Database error: {0}")]
+    Database(String),
+    #[error("Database unavailable: {0}")]
+    Unavailable(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Validation error: {0}")]
+    Validation(String),
+    #[error("Unexpected error: {0}")]
+    Other(#[from] anyhow::Error),
+}
+
+// The assignment's skills come back as three parallel arrays of ids, slugs and names
+const ASSIGNMENT_COLUMNS: &str = "
+    a.assignment_id, a.class_id, a.title, a.target_mastery, a.question_count, a.due_at, a.created_at,
+    ARRAY(
+        SELECT s.skill_id
+        FROM assignment_skills ask
+        INNER JOIN skills s ON s.skill_id = ask.skill_id
+        WHERE ask.assignment_id = a.assignment_id
+        ORDER BY s.skill_name, s.skill_id
+    ),
+    ARRAY(
+        SELECT s.slug
+        FROM assignment_skills ask
+        INNER JOIN skills s ON s.skill_id = ask.skill_id
+        WHERE ask.assignment_id = a.assignment_id
+        ORDER BY s.skill_name, s.skill_id
+    ),
+    ARRAY(
+        SELECT s.skill_name
+        FROM assignment_skills ask
+        INNER JOIN skills s ON s.skill_id = ask.skill_id
+        WHERE ask.assignment_id = a.assignment_id
+        ORDER BY s.skill_name, s.skill_id
+    )
+";
+
+// Index of the first column following ASSIGNMENT_COLUMNS
+const ASSIGNMENT_COLUMN_COUNT: usize = 10;
+
+fn row_to_assignment(row: &Row) -> Assignment {
+    let due_at: DateTime<Utc> = row.get(5);
+    let created_at: DateTime<Utc> = row.get(6);
+    let skill_ids: Vec<i32> = row.get(7);
+    let skill_slugs: Vec<String> = row.get(8);
+    let skill_names: Vec<String> = row.get(9);
+    Assignment {
+        assignment_id: row.get(0),
+        class_id: row.get(1),
+        title: row.get(2),
+        target_mastery: row.get(3),
+        question_count: row.get(4),
+        due_at: due_at.to_rfc3339(),
+        created_at: created_at.to_rfc3339(),
+        skills: skill_ids
+            .into_iter()
+            .zip(skill_slugs)
+            .zip(skill_names)
+            .map(|((skill_id, skill_slug), skill_name)| Skill { skill_id, skill_slug, skill_name })
+            .collect(),
+    }
+}
+
+fn skill_progress(assignment: &Assignment, skill: Skill, questions_answered: i32, mastery: f64) -> AssignmentSkillProgress {
+    AssignmentSkillProgress {
+        skill,
+        questions_answered,
+        mastery,
+        complete: questions_answered >= assignment.question_count && mastery >= assignment.target_mastery,
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn create_assignment(class_id: i32, new_assignment: AssignmentCreate) -> Result<Assignment, AssignmentError> {
+    if new_assignment.title.trim().is_empty() {
+        return Err(AssignmentError::Validation("Assignment title must be non-empty".to_string()));
+    }
+    if new_assignment.skills.is_empty() {
+        return Err(AssignmentError::Validation("Assignment must target at least one skill".to_string()));
+    }
+    if !(new_assignment.target_mastery > 0.0 && new_assignment.target_mastery <= 1.0) {
+        return Err(AssignmentError::Validation("Target mastery must be between 0 and 1".to_string()));
+    }
+    if new_assignment.question_count < 1 {
+        return Err(AssignmentError::Validation("Question count must be at least 1".to_string()));
+    }
+    let due_at = DateTime::parse_from_rfc3339(&new_assignment.due_at)
+        .map_err(|e| AssignmentError::Validation(format!("Invalid due date: {e}")))?
+        .with_timezone(&Utc);
+
+    let mut client = get_client().await
+        .map_err(|e| AssignmentError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let transaction = client.transaction()
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to start transaction: {e}")))?;
+
+    let assignment_id: i32 = transaction.query_one(
+        "
+        INSERT INTO assignments (class_id, title, target_mastery, question_count, due_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING assignment_id
+        ",
+        &[&class_id, &new_assignment.title.trim(), &new_assignment.target_mastery, &new_assignment.question_count, &due_at]
+    )
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to create assignment: {e}")))?
+        .get(0);
+
+    let resolved = transaction.query(
+        "
+        SELECT requested.key, s.skill_id
+        FROM unnest($1::text[]) AS requested(key)
+        LEFT JOIN skills s ON s.skill_id::text = requested.key OR s.slug = requested.key OR s.skill_name = requested.key
+        ",
+        &[&new_assignment.skills]
+    )
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to resolve assignment skills: {e}")))?;
+
+    let unknown: Vec<String> = resolved.iter()
+        .filter(|row| row.get::<_, Option<i32>>(1).is_none())
+        .map(|row| row.get(0))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(AssignmentError::Validation(format!("Unknown skills: {}", unknown.join(", "))));
+    }
+
+    let mut skill_ids: Vec<i32> = resolved.iter().filter_map(|row| row.get(1)).collect();
+    skill_ids.sort();
+    skill_ids.dedup();
+
+    transaction.execute(
+        "INSERT INTO assignment_skills (assignment_id, skill_id) SELECT $1, unnest($2::int4[])",
+        &[&assignment_id, &skill_ids]
+    )
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to link assignment skills: {e}")))?;
+
+    let row = transaction.query_one(
+        &format!("SELECT {ASSIGNMENT_COLUMNS} FROM assignments a WHERE a.assignment_id = $1"),
+        &[&assignment_id]
+    )
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to fetch assignment: {e}")))?;
+
+    transaction.commit()
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to commit assignment: {e}")))?;
+
+    Ok(row_to_assignment(&row))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn list_class_assignments(class_id: i32) -> Result<Vec<Assignment>, AssignmentError> {
+    let client = get_client().await
+        .map_err(|e| AssignmentError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let rows = client.query(
+        &format!("SELECT {ASSIGNMENT_COLUMNS} FROM assignments a WHERE a.class_id = $1 ORDER BY a.due_at"),
+        &[&class_id]
+    )
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to fetch assignments: {e}")))?;
+
+    Ok(rows.iter().map(row_to_assignment).collect())
+}
+
+/// Assignments that are not yet due in every class the student belongs to.
+#[tracing::instrument(skip_all)]
+pub async fn get_open_assignments(user_id: i32) -> Result<Vec<StudentAssignment>, AssignmentError> {
+    let client = get_client().await
+        .map_err(|e| AssignmentError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let rows = client.query(
+        &format!(
+            "
+            SELECT {ASSIGNMENT_COLUMNS}, c.class_name
+            FROM assignments a
+            INNER JOIN classes c ON c.class_id = a.class_id
+            INNER JOIN class_members cm ON cm.class_id = a.class_id
+            WHERE cm.user_id = $1 AND a.due_at > now()
+            ORDER BY a.due_at
+            "
+        ),
+        &[&user_id]
+    )
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to fetch open assignments: {e}")))?;
+
+    let mut open_assignments = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        let assignment = row_to_assignment(&row);
+        let class_name: String = row.get(ASSIGNMENT_COLUMN_COUNT);
+
+        let progress_rows = client
+            .query(
+                "
+                SELECT s.skill_id, s.slug, s.skill_name, COALESCE(ap.questions_answered, 0), COALESCE(ap.mastery, p.progression, 0)
+                FROM assignment_skills ask
+                INNER JOIN skills s ON s.skill_id = ask.skill_id
+                LEFT JOIN assignment_progress ap
+                    ON ap.assignment_id = ask.assignment_id AND ap.skill_id = ask.skill_id AND ap.user_id = $2
+                LEFT JOIN progression p ON p.skill_id = ask.skill_id AND p.user_id = $2
+                WHERE ask.assignment_id = $1
+                ORDER BY s.skill_name
+                ",
+                &[&assignment.assignment_id, &user_id],
+            )
+            .await
+            .map_err(|e| AssignmentError::Database(format!("Failed to fetch assignment progress: {e}")))?;
+
+        let skills: Vec<AssignmentSkillProgress> = progress_rows
+            .into_iter()
+            .map(|row| skill_progress(&assignment, row_to_skill(&row, 0), row.get(3), row.get(4)))
+            .collect();
+        let complete = skills.iter().all(|skill| skill.complete);
+
+        open_assignments.push(StudentAssignment { assignment, class_name, skills, complete });
+    }
+
+    Ok(open_assignments)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn get_assignment_status(class_id: i32, assignment_id: i32) -> Result<AssignmentStatus, AssignmentError> {
+    let client = get_client().await
+        .map_err(|e| AssignmentError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let assignment = client.query_opt(
+        &format!("SELECT {ASSIGNMENT_COLUMNS} FROM assignments a WHERE a.assignment_id = $1 AND a.class_id = $2"),
+        &[&assignment_id, &class_id]
+    )
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to fetch assignment: {e}")))?
+        .map(|row| row_to_assignment(&row))
+        .ok_or_else(|| AssignmentError::NotFound(format!("No assignment with id {assignment_id}")))?;
+
+    let rows = client
+        .query(
+            "
+            SELECT u.user_id, u.first_name, u.last_name, u.username, s.skill_id, s.slug, s.skill_name,
+                   COALESCE(ap.questions_answered, 0), COALESCE(ap.mastery, p.progression, 0)
+            FROM class_members cm
+            INNER JOIN users u ON u.user_id = cm.user_id
+            INNER JOIN assignment_skills ask ON ask.assignment_id = $2
+            INNER JOIN skills s ON s.skill_id = ask.skill_id
+            LEFT JOIN assignment_progress ap
+                ON ap.assignment_id = ask.assignment_id AND ap.skill_id = ask.skill_id AND ap.user_id = cm.user_id
+            LEFT JOIN progression p ON p.skill_id = ask.skill_id AND p.user_id = cm.user_id
+            WHERE cm.class_id = $1
+            ORDER BY u.last_name, u.first_name, u.user_id, s.skill_name
+            ",
+            &[&class_id, &assignment_id],
+        )
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to fetch assignment progress: {e}")))?;
+
+    let mut students: Vec<AssignmentStudentStatus> = Vec::new();
+
+    for row in rows {
+        let user_id: i32 = row.get(0);
+        let progress = skill_progress(&assignment, row_to_skill(&row, 4), row.get(7), row.get(8));
+
+        match students.last_mut() {
+            Some(student) if student.user_id == user_id => student.skills.push(progress),
+            _ => students.push(AssignmentStudentStatus {
+                user_id,
+                first_name: row.get(1),
+                last_name: row.get(2),
+                username: row.get(3),
+                skills: vec![progress],
+                complete: false,
+            }),
+        }
+    }
+
+    for student in students.iter_mut() {
+        student.complete = student.skills.iter().all(|skill| skill.complete);
+    }
+    let completed_students = students.iter().filter(|student| student.complete).count();
+
+    Ok(AssignmentStatus { assignment, completed_students, students })
+}
+
+/// Counts an answer towards every open assignment the student has for this skill.
+#[tracing::instrument(skip_all)]
+pub async fn record_assignment_answer(user_id: i32, skill_id: i32, mastery: f64) -> Result<(), AssignmentError> {
+    let client = get_client().await
+        .map_err(|e| AssignmentError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    client
+        .execute(
+            "
+            INSERT INTO assignment_progress (assignment_id, user_id, skill_id, questions_answered, mastery)
+            SELECT a.assignment_id, cm.user_id, ask.skill_id, 1, $3
+            FROM assignments a
+            INNER JOIN assignment_skills ask ON ask.assignment_id = a.assignment_id
+            INNER JOIN class_members cm ON cm.class_id = a.class_id
+            WHERE cm.user_id = $1 AND ask.skill_id = $2 AND a.due_at > now()
+            ON CONFLICT (assignment_id, user_id, skill_id) DO UPDATE
+            SET questions_answered = assignment_progress.questions_answered + 1,
+                mastery = EXCLUDED.mastery,
+                updated_at = now()
+            ",
+            &[&user_id, &skill_id, &mastery],
+        )
+        .await
+        .map_err(|e| AssignmentError::Database(format!("Failed to record assignment progress: {e}")))?;
+
+    Ok(())
+}
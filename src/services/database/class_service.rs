@@ -0,0 +1,258 @@
+use chrono::{DateTime, Utc};
+use rand_core::{OsRng, RngCore};
+use tokio_postgres::Row;
+
+use crate::{
+    enums::role::Role,
+    services::database::{database::get_client, knowledge_service::row_to_skill},
+    structs::{
+        claims::Claims,
+        class::{Class, ClassStudent, SkillAverage},
+        skill_progression::SkillProgression,
+    },
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ClassError {
+    #[error("Database error: {0}")]
+    Database(String),
+    #[error("Database unavailable: {0}")]
+    Unavailable(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Validation error: {0}")]
+    Validation(String),
+    #[error("Unexpected error: {0}")]
+    Other(#[from] anyhow::Error),
+}
+
+// Letters and digits that are hard to confuse when read aloud or copied from a board
+const JOIN_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const JOIN_CODE_LENGTH: usize = 8;
+const JOIN_CODE_ATTEMPTS: usize = 5;
+
+fn generate_join_code() -> String {
+    let mut bytes = [0u8; JOIN_CODE_LENGTH];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+        .iter()
+        .map(|b| JOIN_CODE_ALPHABET[*b as usize % JOIN_CODE_ALPHABET.len()] as char)
+        .collect()
+}
+
+fn row_to_class(row: &Row) -> Class {
+    let created_at: DateTime<Utc> = row.get(4);
+    Class {
+        class_id: row.get(0),
+        class_name: row.get(1),
+        join_code: row.get(2),
+        teacher_id: row.get(3),
+        created_at: created_at.to_rfc3339(),
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn create_class(teacher_id: i32, class_name: &str) -> Result<Class, ClassError> {
+    if class_name.trim().is_empty() {
+        return Err(ClassError::Validation("Class name must be non-empty".to_string()));
+    }
+
+    let client = get_client().await
+        .map_err(|e| ClassError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    for _ in 0..JOIN_CODE_ATTEMPTS {
+        let join_code = generate_join_code();
+        let row = client.query_opt(
+            "
+            INSERT INTO classes (teacher_id, class_name, join_code)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (join_code) DO NOTHING
+            RETURNING class_id, class_name, join_code, teacher_id, created_at
+            ",
+            &[&teacher_id, &class_name.trim(), &join_code]
+        )
+            .await
+            .map_err(|e| ClassError::Database(format!("Failed to create class: {e}")))?;
+
+        if let Some(row) = row {
+            return Ok(row_to_class(&row));
+        }
+    }
+
+    Err(ClassError::Database("Failed to generate a unique join code".to_string()))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn list_classes(teacher_id: i32) -> Result<Vec<Class>, ClassError> {
+    let client = get_client().await
+        .map_err(|e| ClassError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let rows = client.query(
+        "SELECT class_id, class_name, join_code, teacher_id, created_at FROM classes WHERE teacher_id = $1 ORDER BY created_at",
+        &[&teacher_id]
+    )
+        .await
+        .map_err(|e| ClassError::Database(format!("Failed to fetch classes: {e}")))?;
+
+    Ok(rows.iter().map(row_to_class).collect())
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn list_joined_classes(user_id: i32) -> Result<Vec<Class>, ClassError> {
+    let client = get_client().await
+        .map_err(|e| ClassError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let rows = client.query(
+        "
+        SELECT c.class_id, c.class_name, c.join_code, c.teacher_id, c.created_at
+        FROM classes c
+        INNER JOIN class_members cm ON cm.class_id = c.class_id
+        WHERE cm.user_id = $1
+        ORDER BY cm.joined_at
+        ",
+        &[&user_id]
+    )
+        .await
+        .map_err(|e| ClassError::Database(format!("Failed to fetch joined classes: {e}")))?;
+
+    Ok(rows.iter().map(row_to_class).collect())
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn join_class(user_id: i32, join_code: &str) -> Result<Class, ClassError> {
+    let client = get_client().await
+        .map_err(|e| ClassError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let row = client.query_opt(
+        "SELECT class_id, class_name, join_code, teacher_id, created_at FROM classes WHERE join_code = $1",
+        &[&join_code.trim().to_uppercase()]
+    )
+        .await
+        .map_err(|e| ClassError::Database(format!("Failed to look up join code: {e}")))?
+        .ok_or_else(|| ClassError::NotFound("No class with that join code".to_string()))?;
+
+    let class = row_to_class(&row);
+
+    client.execute(
+        "INSERT INTO class_members (class_id, user_id) VALUES ($1, $2) ON CONFLICT (class_id, user_id) DO NOTHING",
+        &[&class.class_id, &user_id]
+    )
+        .await
+        .map_err(|e| ClassError::Database(format!("Failed to join class: {e}")))?;
+
+    Ok(class)
+}
+
+/// Fetches a class the caller is allowed to manage: their own, or any class for admins.
+/// Classes owned by other teachers are reported as not found.
+#[tracing::instrument(skip_all)]
+pub async fn get_managed_class(class_id: i32, claims: &Claims) -> Result<Class, ClassError> {
+    let client = get_client().await
+        .map_err(|e| ClassError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let row = client.query_opt(
+        "SELECT class_id, class_name, join_code, teacher_id, created_at FROM classes WHERE class_id = $1",
+        &[&class_id]
+    )
+        .await
+        .map_err(|e| ClassError::Database(format!("Failed to fetch class: {e}")))?;
+
+    match row.map(|row| row_to_class(&row)) {
+        Some(class) if class.teacher_id == claims.uid || claims.role == Role::Admin => Ok(class),
+        _ => Err(ClassError::NotFound(format!("No class with id {class_id}"))),
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn get_class_roster(class_id: i32) -> Result<Vec<ClassStudent>, ClassError> {
+    let client = get_client().await
+        .map_err(|e| ClassError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let rows = client
+        .query(
+            "
+            SELECT u.user_id, u.first_name, u.last_name, u.username, s.skill_id, s.slug, s.skill_name, p.progression
+            FROM class_members cm
+            INNER JOIN users u ON u.user_id = cm.user_id
+            LEFT JOIN progression p ON p.user_id = cm.user_id
+            LEFT JOIN skills s ON s.skill_id = p.skill_id
+            WHERE cm.class_id = $1
+            ORDER BY u.last_name, u.first_name, u.user_id, s.skill_name
+            ",
+            &[&class_id],
+        )
+        .await
+        .map_err(|e| ClassError::Database(format!("Failed to fetch class members: {e}")))?;
+
+    let mut roster: Vec<ClassStudent> = Vec::new();
+
+    for row in rows {
+        let user_id: i32 = row.get(0);
+        // Students without any progression yet come back once, with no skill
+        let skill_id: Option<i32> = row.get(4);
+        let progression = skill_id.map(|_| SkillProgression { skill: row_to_skill(&row, 4), progression: row.get(7) });
+
+        match roster.last_mut() {
+            Some(student) if student.user_id == user_id => student.progression.extend(progression),
+            _ => roster.push(ClassStudent {
+                user_id,
+                first_name: row.get(1),
+                last_name: row.get(2),
+                username: row.get(3),
+                progression: progression.into_iter().collect(),
+            }),
+        }
+    }
+
+    Ok(roster)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn get_class_skill_averages(class_id: i32) -> Result<Vec<SkillAverage>, ClassError> {
+    let client = get_client().await
+        .map_err(|e| ClassError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let rows = client
+        .query(
+            "
+            SELECT s.skill_id, s.slug, s.skill_name, AVG(p.progression), COUNT(p.user_id)
+            FROM class_members cm
+            INNER JOIN progression p ON p.user_id = cm.user_id
+            INNER JOIN skills s ON s.skill_id = p.skill_id
+            WHERE cm.class_id = $1
+            GROUP BY s.skill_id, s.slug, s.skill_name
+            ORDER BY s.skill_name
+            ",
+            &[&class_id],
+        )
+        .await
+        .map_err(|e| ClassError::Database(format!("Failed to fetch class averages: {e}")))?;
+
+    let averages: Vec<SkillAverage> = rows
+        .into_iter()
+        .map(|row| SkillAverage {
+            skill: row_to_skill(&row, 0),
+            average_progression: row.get(3),
+            student_count: row.get(4),
+        })
+        .collect();
+
+    Ok(averages)
+}
+
+/// Roster entries trimmed to the skills where the student is below `threshold`,
+/// leaving out students who are at or above it on every skill.
+#[tracing::instrument(skip_all)]
+pub async fn get_students_below_threshold(class_id: i32, threshold: f64) -> Result<Vec<ClassStudent>, ClassError> {
+    let roster = get_class_roster(class_id).await?;
+
+    let students: Vec<ClassStudent> = roster
+        .into_iter()
+        .filter_map(|mut student| {
+            student.progression.retain(|skill| skill.progression < threshold);
+            (!student.progression.is_empty()).then_some(student)
+        })
+        .collect();
+
+    Ok(students)
+}
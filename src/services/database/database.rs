@@ -1,10 +1,44 @@
-use std::{env, error::Error};
+use std::{sync::OnceLock, time::Duration};
 
+use deadpool_postgres::{Manager, ManagerConfig, Pool, PoolError, RecyclingMethod, Runtime};
+use tokio_postgres::NoTls;
 
-pub async fn get_connection_string() -> Result<String, Box<dyn Error>> {
-    let user = env::var("POSTGRES_USER")?;
-    let db = env::var("POSTGRES_DB")?;
-    let pass = env::var("POSTGRES_PASSWORD")?;
-    let ip = env::var("POSTGRES_IP")?;
-    Ok(format!("host={} user={} dbname={} password={}",ip,user,db,pass).to_string())
-}
\ No newline at end of file
+use crate::{config::{DatabaseConfig, SslMode}, tls};
+
+pub use deadpool_postgres::Client;
+
+static POOL: OnceLock<Pool> = OnceLock::new();
+
+/// Creates the connection pool every service draws from. Connections are opened lazily,
+/// over TLS unless `sslmode` is `disable`.
+pub fn init(database: &DatabaseConfig) -> Result<(), String> {
+    let manager_config = ManagerConfig { recycling_method: RecyclingMethod::Fast };
+    let manager = match database.sslmode {
+        SslMode::Disable => Manager::from_config(database.pg_config(), NoTls, manager_config),
+        SslMode::Prefer | SslMode::Require => {
+            let connector = tls::postgres_connector(database.ca_file.as_deref())?;
+            Manager::from_config(database.pg_config(), connector, manager_config)
+        }
+    };
+    let timeout = Some(Duration::from_secs(database.connect_timeout));
+
+    let pool = Pool::builder(manager)
+        .max_size(database.pool_size)
+        .runtime(Runtime::Tokio1)
+        .wait_timeout(timeout)
+        .create_timeout(timeout)
+        .build()
+        .map_err(|e| format!("Failed to create database pool: {e}"))?;
+
+    POOL.set(pool).map_err(|_| "Database pool is already created".to_string())
+}
+
+/// The pool created by [`init`].
+pub fn pool() -> &'static Pool {
+    POOL.get().expect("database::init runs at startup")
+}
+
+/// Checks a connection out of the pool, opening a new one when none are idle.
+pub async fn get_client() -> Result<Client, PoolError> {
+    pool().get().await
+}
@@ -1,37 +1,68 @@
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
-use crate::{services::database::account::AccountError, structs::claims::Claims};
-use chrono::Utc;
+use jsonwebtoken::{Algorithm, Header, Validation, decode, decode_header, encode};
+use crate::{
+    config::JwtConfig,
+    enums::role::Role,
+    services::database::{account::AccountError, signing_keys::{signing_key, verification_key}},
+    structs::claims::Claims,
+};
+use chrono::{DateTime, Utc};
 
-pub fn issue_access_token(user_id: i32,secret: &str) -> Result<String, AccountError> {
-    let now = Utc::now().timestamp() as usize;
-    let exp = now + (15 * 60);
+/// A signed access token and the moment it stops being accepted.
+pub struct AccessToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub fn issue_access_token(
+    user_id: i32,
+    session_id: i32,
+    role: Role,
+    audience: Option<&str>,
+    jwt: &JwtConfig,
+) -> Result<AccessToken, AccountError> {
+    let audience = jwt.resolve_audience(audience).map_err(AccountError::TokenCreation)?;
+    let signing_key = signing_key()?;
+    let issued_at = Utc::now();
+    let now = issued_at.timestamp() as usize;
+    let exp = now + jwt.access_token_lifetime as usize;
 
     let claims = Claims {
         sub: user_id.to_string(),
         uid: user_id,
         iat: now,
         exp,
-        iss: "knowledge tracing api".to_string(),
-        aud: "adapt math desktop-app".to_string(),
+        iss: jwt.issuer.clone(),
+        aud: audience.to_string(),
+        sid: Some(session_id),
+        role,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )
-    .map_err(|e| AccountError::TokenCreation(e.to_string()))
-}
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some(signing_key.key_id.clone());
+
+    let token = encode(&header, &claims, &signing_key.encoding_key)
+        .map_err(|e| AccountError::TokenCreation(e.to_string()))?;
 
-pub fn validate_jwt(token: &str, secret: &str) -> Result<Claims, AccountError> {
-    let mut validation = Validation::default();
-    validation.set_audience(&["adapt math desktop-app"]);
-    validation.set_issuer(&["knowledge tracing api"]);
-    decode::<Claims>(
+    Ok(AccessToken {
         token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &validation,
-    )
-    .map(|data| data.claims)
-    .map_err(|e| AccountError::InvalidToken(e.to_string()))
+        expires_at: DateTime::from_timestamp(exp as i64, 0).unwrap_or(issued_at),
+    })
+}
+
+/// Verifies the signature, expiry, issuer and that the audience is one of the configured ones.
+/// Narrower per-route audience checks are left to the auth extractors.
+pub async fn validate_jwt(token: &str, jwt: &JwtConfig) -> Result<Claims, AccountError> {
+    let header = decode_header(token)
+        .map_err(|e| AccountError::InvalidToken(e.to_string()))?;
+    let key_id = header.kid
+        .ok_or_else(|| AccountError::InvalidToken("Token has no key id".to_string()))?;
+    let decoding_key = verification_key(&key_id, jwt).await?;
+
+    let mut validation = Validation::new(Algorithm::EdDSA);
+    validation.set_audience(&jwt.audiences);
+    validation.set_issuer(&[&jwt.issuer]);
+    validation.leeway = jwt.leeway;
+    decode::<Claims>(token, &decoding_key, &validation)
+        .map(|data| data.claims)
+        .map_err(|e| AccountError::InvalidToken(e.to_string()))
 }
@@ -1,54 +1,108 @@
 use chrono::{DateTime, Utc};
-use tokio_postgres::NoTls;
-use crate::{services::database::database::get_connection_string, structs::{knowledge_score_request::KnowledgeScoreRequest, knowledge_score_update::KnowledgeScoreUpdate, skill_progression::{SkillProgression, SkillProgressionWithDate}}};
+use tokio_postgres::{Client, Row};
+use crate::{helpers::{fuzzy_match::closest_matches, slug::slugify}, services::database::database::get_client, structs::{knowledge_score_request::KnowledgeScoreRequest, knowledge_score_update::KnowledgeScoreUpdate, skill::Skill, skill_progression::{SkillProgression, SkillProgressionWithDate}}};
 
 #[derive(thiserror::Error, Debug)]
 pub enum KnowledgeError {
     #[error("Database error: {0}")]
     Database(String),
 
+    #[error("Database unavailable: {0}")]
+    Unavailable(String),
+
     #[error("Invalid knowledge score update: {0}")]
     InvalidInput(String),
 
+    #[error("Unknown skill '{name}'")]
+    SkillNotFound { name: String, suggestions: Vec<String> },
+
+    #[error("No progression recorded for skill '{name}'")]
+    ProgressionNotFound { name: String },
+
     #[error("Unexpected error: {0}")]
     Other(#[from] anyhow::Error),
 }
 
-pub async fn get_knowledge_score(skill_request: KnowledgeScoreRequest) -> Result<f64, KnowledgeError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to build connection string: {e}")))?;
+/// Starting mastery for every skill a user has not practised yet. `run_migrations` copies it into
+/// `progression_settings` for the trigger that backfills skills added later.
+pub const INITIAL_PROGRESSION: f64 = 0.1;
 
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to connect to DB: {e}")))?;
+const MAX_SKILL_SUGGESTIONS: usize = 3;
 
-    // Spawn connection handler
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
+/// Reads a skill from three consecutive columns, `skill_id, slug, skill_name`, starting at `first`.
+pub(crate) fn row_to_skill(row: &Row, first: usize) -> Skill {
+    Skill {
+        skill_id: row.get(first),
+        skill_slug: row.get(first + 1),
+        skill_name: row.get(first + 2),
+    }
+}
 
-    let row = client.query_one("SELECT progression FROM progression WHERE user_id=$1 AND skill_id=$2",&[&skill_request.student_id, &skill_request.skill_id])
+/// Builds the error for a skill that is not in the catalogue, suggesting close slugs.
+async fn skill_not_found(client: &Client, key: &str) -> KnowledgeError {
+    let suggestions = match client.query("SELECT slug FROM skills", &[]).await {
+        Ok(rows) => {
+            let slugs: Vec<String> = rows.into_iter().map(|row| row.get(0)).collect();
+            closest_matches(&slugify(key), slugs.iter().map(String::as_str), MAX_SKILL_SUGGESTIONS)
+        },
+        Err(e) => {
+            tracing::warn!("Failed to fetch skills for suggestions: {e}");
+            Vec::new()
+        },
+    };
+
+    KnowledgeError::SkillNotFound { name: key.to_string(), suggestions }
+}
+
+/// Looks a skill up by id, slug or display name, preferring them in that order.
+async fn find_skill(client: &Client, key: &str) -> Result<Skill, KnowledgeError> {
+    let row = client
+        .query_opt(
+            "
+            SELECT skill_id, slug, skill_name
+            FROM skills
+            WHERE skill_id::text = $1 OR slug = $1 OR skill_name = $1
+            ORDER BY skill_id::text = $1 DESC, slug = $1 DESC
+            LIMIT 1
+            ",
+            &[&key],
+        )
         .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to get knowledge score: {e}")))?;
+        .map_err(|e| KnowledgeError::Database(format!("Failed to fetch skill: {e}")))?;
 
-    Ok(row.get(0))
+    match row {
+        Some(row) => Ok(row_to_skill(&row, 0)),
+        None => Err(skill_not_found(client, key).await),
+    }
 }
 
-pub async fn update_knowledge_score(update: KnowledgeScoreUpdate) -> Result<(), KnowledgeError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to build connection string: {e}")))?;
+#[tracing::instrument(skip_all)]
+pub async fn get_knowledge_score(skill_request: KnowledgeScoreRequest) -> Result<f64, KnowledgeError> {
+    let client = get_client().await
+        .map_err(|e| KnowledgeError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
+    let row = client
+        .query_opt(
+            "
+            SELECT s.skill_name, p.progression
+            FROM skills s
+            LEFT JOIN progression p ON p.skill_id = s.skill_id AND p.user_id = $1
+            WHERE s.skill_id = $2
+            ",
+            &[&skill_request.student_id, &skill_request.skill_id],
+        )
         .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to connect to DB: {e}")))?;
+        .map_err(|e| KnowledgeError::Database(format!("Failed to get knowledge score: {e}")))?
+        .ok_or_else(|| KnowledgeError::SkillNotFound { name: skill_request.skill_id.to_string(), suggestions: Vec::new() })?;
 
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
+    row.get::<_, Option<f64>>(1)
+        .ok_or_else(|| KnowledgeError::ProgressionNotFound { name: row.get(0) })
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn update_knowledge_score(update: KnowledgeScoreUpdate) -> Result<(), KnowledgeError> {
+    let client = get_client().await
+        .map_err(|e| KnowledgeError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
     client.execute("UPDATE progression SET progression = $1 WHERE user_id=$2 AND skill_id=$3",&[&update.score, &update.student_id, &update.skill_id])
         .await
@@ -57,49 +111,28 @@ pub async fn update_knowledge_score(update: KnowledgeScoreUpdate) -> Result<(),
     Ok(())
 }
 
-pub async fn get_skill_id(skill_name: &str) -> Result<i32, KnowledgeError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to build connection string: {e}")))?;
-
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to connect to DB: {e}")))?;
+/// Resolves a skill path parameter, which may be the skill's id, slug or display name.
+#[tracing::instrument(skip_all)]
+pub async fn get_skill(key: &str) -> Result<Skill, KnowledgeError> {
+    let client = get_client().await
+        .map_err(|e| KnowledgeError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
-    let row = client.query_one("SELECT skill_id FROM SKILLS WHERE skill_name=$1", &[&skill_name])
-        .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to fetch skill id: {e}")))?;
-
-    Ok(row.get(0))
+    find_skill(&client, key).await
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_all_progression_score(user_id: i32) -> Result<Vec<SkillProgression>, KnowledgeError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to build connection string: {e}")))?;
-
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to connect to DB: {e}")))?;
-
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
-    let rows = client.query("SELECT SKILLS.skill_name, PROGRESSION.progression FROM PROGRESSION INNER JOIN SKILLS ON SKILLS.skill_id = PROGRESSION.skill_id WHERE user_id = $1", &[&user_id])
+    let client = get_client().await
+        .map_err(|e| KnowledgeError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+    let rows = client.query("SELECT SKILLS.skill_id, SKILLS.slug, SKILLS.skill_name, PROGRESSION.progression FROM PROGRESSION INNER JOIN SKILLS ON SKILLS.skill_id = PROGRESSION.skill_id WHERE user_id = $1", &[&user_id])
         .await
         .map_err(|e| KnowledgeError::Database(format!("Failed to gather skills: {e}")))?;
     let progression: Vec<SkillProgression> = rows
     .into_iter()
     .map(|row| {
-        let skill_name: String = row.get(0);
-        let progression: f64 = row.get(1); 
+        let progression: f64 = row.get(3);
         SkillProgression {
-            skill_name,
+            skill: row_to_skill(&row, 0),
             progression,
         }
     })
@@ -107,44 +140,28 @@ pub async fn get_all_progression_score(user_id: i32) -> Result<Vec<SkillProgress
 
     Ok(progression)
 }
-pub async fn log_progress(user_id: i32, skill_name: &str) -> Result<(), KnowledgeError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to build connection string: {e}")))?;
-
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to connect to DB: {e}")))?;
+#[tracing::instrument(skip_all)]
+pub async fn log_progress(user_id: i32, skill_key: &str) -> Result<(), KnowledgeError> {
+    let client = get_client().await
+        .map_err(|e| KnowledgeError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
+    let skill = find_skill(&client, skill_key).await?;
 
     let row = client
-        .query_one(
-            "SELECT skill_id FROM skills WHERE skill_name = $1",
-            &[&skill_name],
-        )
-        .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to fetch skill id: {e}")))?;
-
-    let skill_id: i32 = row.get(0);
-
-    let row = client
-        .query_one(
+        .query_opt(
             "SELECT progression FROM progression WHERE user_id = $1 AND skill_id = $2",
-            &[&user_id, &skill_id],
+            &[&user_id, &skill.skill_id],
         )
         .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to fetch current progression: {e}")))?;
+        .map_err(|e| KnowledgeError::Database(format!("Failed to fetch current progression: {e}")))?
+        .ok_or_else(|| KnowledgeError::ProgressionNotFound { name: skill.skill_name.clone() })?;
 
     let current_progression: f64 = row.get(0);
 
     client
         .execute(
             "INSERT INTO historical_progression (user_id, skill_id, progression) VALUES ($1, $2, $3)",
-            &[&user_id, &skill_id, &current_progression],
+            &[&user_id, &skill.skill_id, &current_progression],
         )
         .await
         .map_err(|e| KnowledgeError::Database(format!("Failed to log historical progression: {e}")))?;
@@ -152,68 +169,52 @@ pub async fn log_progress(user_id: i32, skill_name: &str) -> Result<(), Knowledg
     Ok(())
 }
 
-pub async fn get_historical_skills(user_id: i32) -> Result<Vec<String>, KnowledgeError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to build connection string: {e}")))?;
-
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to connect to DB: {e}")))?;
-
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
+#[tracing::instrument(skip_all)]
+pub async fn get_historical_skills(user_id: i32) -> Result<Vec<Skill>, KnowledgeError> {
+    let client = get_client().await
+        .map_err(|e| KnowledgeError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
     let rows = client
         .query(
             "
-            SELECT DISTINCT s.skill_name
+            SELECT DISTINCT s.skill_id, s.slug, s.skill_name
             FROM historical_progression hp
             INNER JOIN skills s ON s.skill_id = hp.skill_id
             WHERE hp.user_id = $1
+            ORDER BY s.skill_id
             ",
             &[&user_id],
         )
         .await
         .map_err(|e| KnowledgeError::Database(format!("Failed to fetch historical skills: {e}")))?;
 
-    let skills: Vec<String> = rows
-        .into_iter()
-        .map(|row| row.get(0))
+    let skills: Vec<Skill> = rows
+        .iter()
+        .map(|row| row_to_skill(row, 0))
         .collect();
 
     Ok(skills)
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_skill_history(
     user_id: i32,
-    skill_name: &str
+    skill_key: &str
 ) -> Result<Vec<SkillProgressionWithDate>, KnowledgeError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to build connection string: {e}")))?;
-
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
-        .await
-        .map_err(|e| KnowledgeError::Database(format!("Failed to connect to DB: {e}")))?;
+    let client = get_client().await
+        .map_err(|e| KnowledgeError::Unavailable(format!("Failed to connect to DB: {e}")))?;
 
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
-        }
-    });
+    let skill = find_skill(&client, skill_key).await?;
 
     let rows = client
         .query(
             "
-            SELECT s.skill_name, hp.progression, hp.recorded_at
+            SELECT hp.progression, hp.recorded_at
             FROM historical_progression hp
-            INNER JOIN skills s ON s.skill_id = hp.skill_id
-            WHERE hp.user_id = $1 AND s.skill_name = $2
+            WHERE hp.user_id = $1 AND hp.skill_id = $2
             ORDER BY hp.recorded_at ASC
             ",
-            &[&user_id, &skill_name],
+            &[&user_id, &skill.skill_id],
         )
         .await
         .map_err(|e| KnowledgeError::Database(format!("Failed to fetch skill history: {e}")))?;
@@ -221,12 +222,11 @@ pub async fn get_skill_history(
     let history: Vec<SkillProgressionWithDate> = rows
         .into_iter()
         .map(|row| {
-            let skill_name: String = row.get(0);
-            let progression: f64 = row.get(1);
-            let recorded_at: DateTime<Utc> = row.get(2);
+            let progression: f64 = row.get(0);
+            let recorded_at: DateTime<Utc> = row.get(1);
 
             SkillProgressionWithDate {
-                skill_name,
+                skill: skill.clone(),
                 progression,
                 recorded_at: recorded_at.to_rfc3339(),
             }
@@ -234,4 +234,26 @@ pub async fn get_skill_history(
         .collect();
 
     Ok(history)
+}
+
+/// Inserts a progression row for every user/skill pair that is missing one.
+/// Returns the number of rows added.
+#[tracing::instrument(skip_all)]
+pub async fn backfill_progression() -> Result<u64, KnowledgeError> {
+    let client = get_client().await
+        .map_err(|e| KnowledgeError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    client
+        .execute(
+            "
+            INSERT INTO progression (user_id, skill_id, progression)
+            SELECT u.user_id, s.skill_id, $1
+            FROM users u
+            CROSS JOIN skills s
+            ON CONFLICT (user_id, skill_id) DO NOTHING
+            ",
+            &[&INITIAL_PROGRESSION],
+        )
+        .await
+        .map_err(|e| KnowledgeError::Database(format!("Failed to backfill progression: {e}")))
 }
\ No newline at end of file
@@ -0,0 +1,265 @@
+use chrono::{DateTime, Duration, Utc};
+use deadpool_postgres::{GenericClient, Transaction};
+use metrics::counter;
+
+use crate::{
+    services::database::{account::AccountError, database::{Client, get_client}},
+    shutdown,
+    structs::lockout_event::LockoutEvent,
+};
+
+/// How failed logins against one key (a username or an IP) slow that key down.
+/// After `free_attempts` failures each further attempt has to wait twice as long
+/// as the last, and from `lockout_after` failures the key is locked for `max_delay`.
+struct ThrottlePolicy {
+    scope: &'static str,
+    column: &'static str,
+    free_attempts: i64,
+    lockout_after: i64,
+    base_delay: Duration,
+    max_delay: Duration,
+    window: Duration,
+}
+
+const USERNAME_POLICY: ThrottlePolicy = ThrottlePolicy {
+    scope: "username",
+    column: "username",
+    free_attempts: 3,
+    lockout_after: 10,
+    base_delay: Duration::seconds(1),
+    max_delay: Duration::minutes(15),
+    window: Duration::hours(1),
+};
+
+// Classrooms share an address, so the per-IP limits are much looser
+const IP_POLICY: ThrottlePolicy = ThrottlePolicy {
+    scope: "ip",
+    column: "ip_address",
+    free_attempts: 20,
+    lockout_after: 50,
+    base_delay: Duration::seconds(1),
+    max_delay: Duration::minutes(15),
+    window: Duration::hours(1),
+};
+
+/// How often attempts older than every policy's window are deleted.
+const PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+impl ThrottlePolicy {
+    fn delay(&self, failures: i64) -> Option<Duration> {
+        if failures < self.free_attempts {
+            return None;
+        }
+        if failures >= self.lockout_after {
+            return Some(self.max_delay);
+        }
+        let exponent = (failures - self.free_attempts).min(20) as i32;
+        Some((self.base_delay * 2i32.pow(exponent as u32)).min(self.max_delay))
+    }
+
+    /// Failures for `key` inside the window. For usernames a successful login resets the count;
+    /// for IPs it does not, otherwise one valid account would unlock guessing against all others.
+    async fn recent_failures(&self, client: &impl GenericClient, key: &str) -> Result<(i64, Option<DateTime<Utc>>), AccountError> {
+        let since = Utc::now() - self.window;
+        let reset_on_success = if self.scope == "username" {
+            format!(
+                "AND attempted_at > COALESCE((SELECT max(attempted_at) FROM login_attempts WHERE {0} = $1 AND succeeded), '-infinity')",
+                self.column
+            )
+        } else {
+            String::new()
+        };
+
+        let row = client.query_one(
+            &format!(
+                "SELECT count(*), max(attempted_at) FROM login_attempts WHERE {} = $1 AND NOT succeeded AND attempted_at > $2 {reset_on_success}",
+                self.column
+            ),
+            &[&key, &since]
+        )
+            .await
+            .map_err(|e| AccountError::Database(format!("Failed to count login attempts: {e}")))?;
+
+        Ok((row.get(0), row.get(1)))
+    }
+
+    async fn locked_until(&self, client: &impl GenericClient, key: &str) -> Result<Option<DateTime<Utc>>, AccountError> {
+        let (failures, last_failure) = self.recent_failures(client, key).await?;
+
+        Ok(match (self.delay(failures), last_failure) {
+            (Some(delay), Some(last_failure)) if last_failure + delay > Utc::now() => Some(last_failure + delay),
+            _ => None,
+        })
+    }
+}
+
+async fn connect() -> Result<Client, AccountError> {
+    get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))
+}
+
+fn throttled_keys<'a>(username: &'a str, ip_address: Option<&'a str>) -> Vec<(&'static ThrottlePolicy, &'a str)> {
+    let mut keys = vec![(&USERNAME_POLICY, username)];
+    if let Some(ip_address) = ip_address {
+        keys.push((&IP_POLICY, ip_address));
+    }
+    keys
+}
+
+/// Holds each key's advisory lock until the transaction ends, so concurrent logins against the
+/// same username or IP are counted one after another. Keys are always taken username first.
+async fn lock_keys(transaction: &Transaction<'_>, keys: &[(&'static ThrottlePolicy, &str)]) -> Result<(), AccountError> {
+    for (policy, key) in keys {
+        transaction.execute("SELECT pg_advisory_xact_lock(hashtext($1))", &[&format!("login:{}:{key}", policy.scope)])
+            .await
+            .map_err(|e| AccountError::Database(format!("Failed to lock login attempts: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Rejects the attempt with [`AccountError::Locked`] while either the username or the IP is backing off,
+/// and otherwise records it as a failure before the password is checked, returning its id.
+/// Recording first means a burst of parallel guesses is throttled as if they had arrived in turn;
+/// [`finish_login_attempt`] marks the attempt as succeeded once the password verifies.
+#[tracing::instrument(skip_all)]
+pub async fn begin_login_attempt(username: &str, ip_address: Option<&str>) -> Result<i64, AccountError> {
+    let mut client = connect().await?;
+    let transaction = client.transaction()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to start transaction: {e}")))?;
+
+    let keys = throttled_keys(username, ip_address);
+    lock_keys(&transaction, &keys).await?;
+
+    let mut locked_until = None;
+    for (policy, key) in &keys {
+        locked_until = locked_until.max(policy.locked_until(&transaction, key).await?);
+    }
+    if let Some(until) = locked_until {
+        let retry_after = (until - Utc::now()).num_seconds().max(0) as u64 + 1;
+        return Err(AccountError::Locked(retry_after));
+    }
+
+    let attempt_id: i64 = transaction.query_one(
+        "INSERT INTO login_attempts (username, ip_address, succeeded) VALUES ($1, $2, FALSE) RETURNING attempt_id",
+        &[&username, &ip_address]
+    )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to record login attempt: {e}")))?
+        .get(0);
+
+    transaction.commit()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to record login attempt: {e}")))?;
+
+    Ok(attempt_id)
+}
+
+/// Settles an attempt from [`begin_login_attempt`]. A failure that takes a key to its lockout
+/// threshold logs a lockout event, once per lockout.
+#[tracing::instrument(skip_all)]
+pub async fn finish_login_attempt(attempt_id: i64, username: &str, ip_address: Option<&str>, succeeded: bool) -> Result<(), AccountError> {
+    let mut client = connect().await?;
+
+    counter!("login_attempts_total", "outcome" => if succeeded { "success" } else { "failure" }).increment(1);
+
+    if succeeded {
+        client.execute("UPDATE login_attempts SET succeeded = TRUE WHERE attempt_id = $1", &[&attempt_id])
+            .await
+            .map_err(|e| AccountError::Database(format!("Failed to record login attempt: {e}")))?;
+        return Ok(());
+    }
+
+    let transaction = client.transaction()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to start transaction: {e}")))?;
+
+    let keys = throttled_keys(username, ip_address);
+    lock_keys(&transaction, &keys).await?;
+
+    for (policy, key) in keys {
+        let (failures, _) = policy.recent_failures(&transaction, key).await?;
+        if failures < policy.lockout_after {
+            continue;
+        }
+
+        // Concurrent failures can step past the threshold, so log whichever sees it first
+        let logged = transaction.execute(
+            "
+            INSERT INTO lockout_events (scope, lock_key, failed_attempts, locked_until)
+            SELECT $1, $2, $3, $4
+            WHERE NOT EXISTS (SELECT 1 FROM lockout_events WHERE scope = $1 AND lock_key = $2 AND locked_until > now())
+            ",
+            &[&policy.scope, &key, &failures, &(Utc::now() + policy.max_delay)]
+        )
+            .await
+            .map_err(|e| AccountError::Database(format!("Failed to record lockout: {e}")))?;
+
+        if logged > 0 {
+            counter!("lockouts_total", "scope" => policy.scope).increment(1);
+        }
+    }
+
+    transaction.commit()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to record lockout: {e}")))?;
+
+    Ok(())
+}
+
+async fn prune_login_attempts() -> Result<u64, AccountError> {
+    let client = connect().await?;
+    let cutoff = Utc::now() - USERNAME_POLICY.window.max(IP_POLICY.window);
+
+    client.execute("DELETE FROM login_attempts WHERE attempted_at < $1", &[&cutoff])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to prune login attempts: {e}")))
+}
+
+/// Starts a worker that deletes login attempts no policy counts any more, so the table
+/// only holds the last window of attempts.
+pub fn prune_on_interval() {
+    shutdown::spawn_worker("login_attempt_pruning", async {
+        let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            tokio::select! {
+                () = shutdown::requested() => break,
+                _ = interval.tick() => match prune_login_attempts().await {
+                    Ok(pruned) => tracing::debug!(pruned, "Pruned old login attempts"),
+                    Err(e) => tracing::warn!("Failed to prune login attempts: {e}"),
+                },
+            }
+        }
+    });
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn list_lockout_events(limit: i64) -> Result<Vec<LockoutEvent>, AccountError> {
+    let client = connect().await?;
+
+    let rows = client.query(
+        "SELECT lockout_id, scope, lock_key, failed_attempts, locked_until, created_at FROM lockout_events ORDER BY created_at DESC LIMIT $1",
+        &[&limit]
+    )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to fetch lockout events: {e}")))?;
+
+    let events: Vec<LockoutEvent> = rows
+        .into_iter()
+        .map(|row| {
+            let locked_until: DateTime<Utc> = row.get(4);
+            let created_at: DateTime<Utc> = row.get(5);
+
+            LockoutEvent {
+                lockout_id: row.get(0),
+                scope: row.get(1),
+                lock_key: row.get(2),
+                failed_attempts: row.get(3),
+                locked_until: locked_until.to_rfc3339(),
+                created_at: created_at.to_rfc3339(),
+            }
+        })
+        .collect();
+
+    Ok(events)
+}
@@ -0,0 +1,126 @@
+use crate::services::database::{
+    database::{Client, get_client},
+    knowledge_service::INITIAL_PROGRESSION,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum MigrationError {
+    #[error("Database error: {0}")]
+    Database(String),
+    #[error("Database unavailable: {0}")]
+    Unavailable(String),
+    #[error("Migration {0} failed: {1}")]
+    Failed(i32, String),
+}
+
+/// Schema changes applied on top of the original tables, in order.
+/// Each entry is recorded in `schema_migrations` once it has run.
+pub const MIGRATIONS: &[(i32, &str)] = &[
+    (1, include_str!("../../../migrations/0001_session_metadata.sql")),
+    (2, include_str!("../../../migrations/0002_user_roles.sql")),
+    (3, include_str!("../../../migrations/0003_classes.sql")),
+    (4, include_str!("../../../migrations/0004_assignments.sql")),
+    (5, include_str!("../../../migrations/0005_username_case_insensitive.sql")),
+    (6, include_str!("../../../migrations/0006_backfill_progression_trigger.sql")),
+    (7, include_str!("../../../migrations/0007_login_attempts.sql")),
+    (8, include_str!("../../../migrations/0008_password_reset_tokens.sql")),
+    (9, include_str!("../../../migrations/0009_session_audience.sql")),
+    (10, include_str!("../../../migrations/0010_skill_slugs.sql")),
+    (11, include_str!("../../../migrations/0011_llm_usage.sql")),
+    (12, include_str!("../../../migrations/0012_initial_progression_setting.sql")),
+    (13, include_str!("../../../migrations/0013_reset_token_digest.sql")),
+    (14, include_str!("../../../migrations/0014_llm_usage_questions.sql")),
+];
+
+/// The server's message with its detail and hint, which `tokio_postgres` leaves out of
+/// its own `Display` for errors raised by the database.
+fn describe(error: &tokio_postgres::Error) -> String {
+    error.as_db_error().map_or_else(|| error.to_string(), ToString::to_string)
+}
+
+/// Applies every migration not yet recorded in `schema_migrations`. Runners hold an advisory
+/// lock while they read and apply versions, so services starting together apply each once.
+#[tracing::instrument(skip_all)]
+pub async fn run_migrations() -> Result<(), MigrationError> {
+    let client = get_client().await
+        .map_err(|e| MigrationError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+    // Kept out of the pool so the lock goes with the connection if a migration fails
+    let mut client = Client::take(client);
+
+    client.execute("SELECT pg_advisory_lock(hashtext('schema_migrations'))", &[])
+        .await
+        .map_err(|e| MigrationError::Database(format!("Failed to lock migrations: {e}")))?;
+
+    apply_pending(&mut client).await?;
+
+    client.execute("SELECT pg_advisory_unlock(hashtext('schema_migrations'))", &[])
+        .await
+        .map_err(|e| MigrationError::Database(format!("Failed to unlock migrations: {e}")))?;
+
+    Ok(())
+}
+
+async fn apply_pending(client: &mut tokio_postgres::Client) -> Result<(), MigrationError> {
+    client.batch_execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        )"
+    )
+        .await
+        .map_err(|e| MigrationError::Database(format!("Failed to create migrations table: {e}")))?;
+
+    let applied: Vec<i32> = client.query("SELECT version FROM schema_migrations", &[])
+        .await
+        .map_err(|e| MigrationError::Database(format!("Failed to fetch applied migrations: {e}")))?
+        .into_iter()
+        .map(|row| row.get(0))
+        .collect();
+
+    for (version, sql) in MIGRATIONS {
+        if applied.contains(version) {
+            continue;
+        }
+
+        let transaction = client.transaction()
+            .await
+            .map_err(|e| MigrationError::Database(format!("Failed to start transaction: {e}")))?;
+
+        transaction.batch_execute(sql)
+            .await
+            .map_err(|e| MigrationError::Failed(*version, describe(&e)))?;
+
+        transaction.execute("INSERT INTO schema_migrations (version) VALUES ($1)", &[version])
+            .await
+            .map_err(|e| MigrationError::Failed(*version, e.to_string()))?;
+
+        transaction.commit()
+            .await
+            .map_err(|e| MigrationError::Failed(*version, e.to_string()))?;
+    }
+
+    // Skills added later get their starting progression from the trigger, which reads this
+    client.execute("UPDATE progression_settings SET initial_progression = $1", &[&INITIAL_PROGRESSION])
+        .await
+        .map_err(|e| MigrationError::Database(format!("Failed to store initial progression: {e}")))?;
+
+    Ok(())
+}
+
+/// The newest migration this build knows about.
+pub fn latest_version() -> i32 {
+    MIGRATIONS.last().map_or(0, |(version, _)| *version)
+}
+
+/// The newest migration applied to the database, or 0 when none have run.
+#[tracing::instrument(skip_all)]
+pub async fn schema_version() -> Result<i32, MigrationError> {
+    let client = get_client().await
+        .map_err(|e| MigrationError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let row = client.query_one("SELECT COALESCE(MAX(version), 0) FROM schema_migrations", &[])
+        .await
+        .map_err(|e| MigrationError::Database(format!("Failed to fetch schema version: {e}")))?;
+
+    Ok(row.get(0))
+}
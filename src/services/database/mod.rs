@@ -2,4 +2,10 @@ pub mod knowledge_service;
 pub mod question_service;
 pub mod database;
 pub mod account;
-pub mod jwt;
\ No newline at end of file
+pub mod jwt;
+pub mod migrations;
+pub mod class_service;
+pub mod assignment_service;
+pub mod login_attempts;
+pub mod personal_data;
+pub mod signing_keys;
\ No newline at end of file
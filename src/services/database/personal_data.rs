@@ -0,0 +1,195 @@
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    enums::role::Role,
+    middleware::client_info::ClientInfo,
+    services::database::{
+        account::{AccountError, fetch_details, list_sessions},
+        login_attempts::{begin_login_attempt, finish_login_attempt},
+        class_service::{list_classes, list_joined_classes},
+        database::get_client,
+        knowledge_service::{get_all_progression_score, row_to_skill},
+    },
+    structs::{
+        claims::Claims,
+        data_export::{AccountDetails, AssignmentProgressRecord, DataExport, LoginAttempt},
+        skill_progression::SkillProgressionWithDate,
+    },
+};
+
+/// Collects everything held about the caller into a single archive.
+#[tracing::instrument(skip_all)]
+pub async fn export_user_data(claims: &Claims) -> Result<DataExport, AccountError> {
+    let user_id = claims.uid;
+
+    let account = fetch_details(claims).await?;
+    let progression = get_all_progression_score(user_id)
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to export progression: {e}")))?;
+    let sessions = list_sessions(claims).await?;
+    let class_memberships = list_joined_classes(user_id)
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to export class memberships: {e}")))?;
+    let classes_taught = list_classes(user_id)
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to export classes: {e}")))?;
+
+    let client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let historical_progression: Vec<SkillProgressionWithDate> = client
+        .query(
+            "
+            SELECT s.skill_id, s.slug, s.skill_name, hp.progression, hp.recorded_at
+            FROM historical_progression hp
+            INNER JOIN skills s ON s.skill_id = hp.skill_id
+            WHERE hp.user_id = $1
+            ORDER BY hp.recorded_at ASC
+            ",
+            &[&user_id],
+        )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to export historical progression: {e}")))?
+        .into_iter()
+        .map(|row| {
+            let recorded_at: DateTime<Utc> = row.get(4);
+            SkillProgressionWithDate {
+                skill: row_to_skill(&row, 0),
+                progression: row.get(3),
+                recorded_at: recorded_at.to_rfc3339(),
+            }
+        })
+        .collect();
+
+    let login_attempts: Vec<LoginAttempt> = client
+        .query(
+            "SELECT ip_address, succeeded, attempted_at FROM login_attempts WHERE username = lower($1) ORDER BY attempted_at ASC",
+            &[&account.username],
+        )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to export login attempts: {e}")))?
+        .into_iter()
+        .map(|row| {
+            let attempted_at: DateTime<Utc> = row.get(2);
+            LoginAttempt {
+                ip_address: row.get(0),
+                succeeded: row.get(1),
+                attempted_at: attempted_at.to_rfc3339(),
+            }
+        })
+        .collect();
+
+    let assignment_progress: Vec<AssignmentProgressRecord> = client
+        .query(
+            "
+            SELECT ap.assignment_id, s.skill_id, s.slug, s.skill_name, ap.questions_answered, ap.mastery, ap.updated_at
+            FROM assignment_progress ap
+            INNER JOIN skills s ON s.skill_id = ap.skill_id
+            WHERE ap.user_id = $1
+            ORDER BY ap.assignment_id, s.skill_name
+            ",
+            &[&user_id],
+        )
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to export assignment progress: {e}")))?
+        .into_iter()
+        .map(|row| {
+            let updated_at: DateTime<Utc> = row.get(6);
+            AssignmentProgressRecord {
+                assignment_id: row.get(0),
+                skill: row_to_skill(&row, 1),
+                questions_answered: row.get(4),
+                mastery: row.get(5),
+                updated_at: updated_at.to_rfc3339(),
+            }
+        })
+        .collect();
+
+    let role: String = client.query_one("SELECT role FROM users WHERE user_id = $1", &[&user_id])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to export role: {e}")))?
+        .get(0);
+
+    Ok(DataExport {
+        exported_at: Utc::now().to_rfc3339(),
+        account: AccountDetails {
+            user_id,
+            first_name: account.first_name,
+            last_name: account.last_name,
+            username: account.username,
+            role: role.parse::<Role>().map_err(AccountError::Database)?,
+        },
+        progression,
+        historical_progression,
+        sessions,
+        login_attempts,
+        class_memberships,
+        classes_taught,
+        assignment_progress,
+    })
+}
+
+/// Permanently removes the account and everything linked to it in one transaction.
+/// Classes the user teaches are deleted along with their assignments and memberships.
+#[tracing::instrument(skip_all)]
+pub async fn delete_account(user_id: i32, password: &str, client_info: &ClientInfo) -> Result<(), AccountError> {
+    let mut client = get_client().await
+        .map_err(|e| AccountError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let row = client.query_one("SELECT username, password_hash FROM users WHERE user_id = $1", &[&user_id])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to find user: {e}")))?;
+    let username: String = row.get(0);
+    let hash: String = row.get(1);
+
+    let parsed_hash = PasswordHash::new(&hash)
+        .map_err(|e| AccountError::Hashing(format!("Failed to parse stored hash: {e}")))?;
+
+    // Counted like a login, so a stolen access token cannot be used to guess the password
+    let attempt_key = username.to_lowercase();
+    let ip_address = client_info.ip_address.as_deref();
+    let attempt_id = begin_login_attempt(&attempt_key, ip_address).await?;
+    let verified = Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_ok();
+    finish_login_attempt(attempt_id, &attempt_key, ip_address, verified).await?;
+    if !verified {
+        return Err(AccountError::Authentication("Password is incorrect".to_string()));
+    }
+
+    let transaction = client.transaction()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to start transaction: {e}")))?;
+
+    let statements = [
+        ("DELETE FROM sessions WHERE user_id = $1", "sessions"),
+        ("DELETE FROM password_reset_tokens WHERE user_id = $1", "reset tokens"),
+        ("UPDATE password_reset_tokens SET issued_by = NULL WHERE issued_by = $1", "issued reset tokens"),
+        ("DELETE FROM assignment_progress WHERE user_id = $1", "assignment progress"),
+        ("DELETE FROM class_members WHERE user_id = $1", "class memberships"),
+        ("DELETE FROM classes WHERE teacher_id = $1", "classes"),
+        ("DELETE FROM historical_progression WHERE user_id = $1", "historical progression"),
+        ("DELETE FROM progression WHERE user_id = $1", "progression"),
+        ("DELETE FROM llm_usage WHERE user_id = $1", "word problem usage"),
+    ];
+    for (sql, description) in statements {
+        transaction.execute(sql, &[&user_id])
+            .await
+            .map_err(|e| AccountError::Database(format!("Failed to delete {description}: {e}")))?;
+    }
+
+    transaction.execute("DELETE FROM login_attempts WHERE username = lower($1)", &[&username])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to delete login attempts: {e}")))?;
+    transaction.execute("DELETE FROM lockout_events WHERE scope = 'username' AND lock_key = lower($1)", &[&username])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to delete lockout events: {e}")))?;
+    transaction.execute("DELETE FROM users WHERE user_id = $1", &[&user_id])
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to delete user: {e}")))?;
+
+    transaction.commit()
+        .await
+        .map_err(|e| AccountError::Database(format!("Failed to commit account deletion: {e}")))?;
+
+    Ok(())
+}
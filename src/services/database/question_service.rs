@@ -1,40 +1,119 @@
-use postgres::NoTls;
+use chrono::{Days, NaiveDate, NaiveTime, Utc};
 
-use crate::services::database::database::get_connection_string;
+use crate::{
+    services::database::{database::get_client, knowledge_service::row_to_skill},
+    structs::skill::Skill,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum GeneratorError {
     #[error("Database error: {0}")]
     Database(String),
+    #[error("Database unavailable: {0}")]
+    Unavailable(String),
     #[error("Authentication error: {0}")]
     Authentication(String),
     #[error("Validation error: {0}")]
     Validation(String),
+    /// Carries the seconds until the budget resets at UTC midnight
+    #[error("Daily word problem budget used up")]
+    BudgetExhausted(u64),
     #[error("Unexpected error: {0}")]
     Other(#[from] anyhow::Error),
 }
 
 
-pub async fn get_module_names() -> Result<Vec<String>, GeneratorError> {
-    let connection_string = get_connection_string().await
-        .map_err(|e| GeneratorError::Database(format!("Failed to build connection string: {e}")))?;
-
-    let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
+#[tracing::instrument(skip_all)]
+pub async fn list_skills() -> Result<Vec<Skill>, GeneratorError> {
+    let client = get_client().await
+        .map_err(|e| GeneratorError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+    let rows = client.query("SELECT skill_id, slug, skill_name FROM SKILLS ORDER BY skill_id", &[])
         .await
-        .map_err(|e| GeneratorError::Database(format!("Failed to connect to DB: {e}")))?;
+        .map_err(|e| GeneratorError::Database(format!("Failed to fetch topics from database: {e}")))?;
+
+    Ok(rows.iter().map(|row| row_to_skill(row, 0)).collect())
+}
 
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Postgres connection error: {e}");
+fn seconds_until_utc_midnight() -> u64 {
+    let now = Utc::now();
+    let midnight = (now.date_naive() + Days::new(1)).and_time(NaiveTime::MIN).and_utc();
+    (midnight - now).num_seconds().max(1) as u64
+}
+
+/// Part of a user's daily word problem budget, taken before the questions are generated.
+/// Dropping it without [`LlmReservation::keep`] hands the allowance back, so a failed
+/// generation or a request cut short by the timeout does not use it up.
+pub struct LlmReservation {
+    user_id: i32,
+    usage_date: NaiveDate,
+    amount: i32,
+    kept: bool,
+}
+
+impl LlmReservation {
+    /// Charges the reservation for good once the questions are ready to be served.
+    pub fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for LlmReservation {
+    fn drop(&mut self) {
+        if self.kept {
+            return;
         }
-    });
-    let rows = client.query("SELECT skill_name FROM SKILLS", &[])
+
+        let (user_id, usage_date, amount) = (self.user_id, self.usage_date, self.amount);
+        tokio::spawn(async move {
+            if let Err(e) = refund_llm_budget(user_id, usage_date, amount).await {
+                tracing::warn!(user_id, "Failed to refund word problem budget: {e}");
+            }
+        });
+    }
+}
+
+/// Reserves `amount` word problems from the user's budget of `budget` per UTC day, failing
+/// when that would go over it. A budget of 0 is unlimited, though usage is still recorded.
+#[tracing::instrument(skip_all)]
+pub async fn reserve_llm_budget(user_id: i32, amount: usize, budget: u32) -> Result<LlmReservation, GeneratorError> {
+    let client = get_client().await
+        .map_err(|e| GeneratorError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    let amount = i32::try_from(amount).unwrap_or(i32::MAX);
+    let budget = i32::try_from(budget).unwrap_or(i32::MAX);
+
+    // Checked and charged in one statement, so concurrent requests cannot overspend between them
+    let reserved = client.query_opt(
+        "
+        INSERT INTO llm_usage (user_id, usage_date, questions)
+        SELECT $1, (now() AT TIME ZONE 'UTC')::date, $2
+        WHERE $3 = 0 OR $2 <= $3
+        ON CONFLICT (user_id, usage_date) DO UPDATE
+        SET questions = llm_usage.questions + $2
+        WHERE $3 = 0 OR llm_usage.questions + $2 <= $3
+        RETURNING usage_date
+        ",
+        &[&user_id, &amount, &budget],
+    )
         .await
-        .map_err(|e| GeneratorError::Database(format!("Failed to fetch topics from database: {e}")))?;
-    let mut topics: Vec<String> = [].to_vec();
-    for row in rows {
-        let topic: String = row.get(0);
-        topics.push(topic);
+        .map_err(|e| GeneratorError::Database(format!("Failed to reserve LLM budget: {e}")))?;
+
+    match reserved {
+        Some(row) => Ok(LlmReservation { user_id, usage_date: row.get(0), amount, kept: false }),
+        None => Err(GeneratorError::BudgetExhausted(seconds_until_utc_midnight())),
     }
-    Ok(topics)
-}
\ No newline at end of file
+}
+
+async fn refund_llm_budget(user_id: i32, usage_date: NaiveDate, amount: i32) -> Result<(), GeneratorError> {
+    let client = get_client().await
+        .map_err(|e| GeneratorError::Unavailable(format!("Failed to connect to DB: {e}")))?;
+
+    client.execute(
+        "UPDATE llm_usage SET questions = GREATEST(questions - $3, 0) WHERE user_id = $1 AND usage_date = $2",
+        &[&user_id, &usage_date, &amount],
+    )
+        .await
+        .map_err(|e| GeneratorError::Database(format!("Failed to refund LLM budget: {e}")))?;
+
+    Ok(())
+}
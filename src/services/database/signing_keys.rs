@@ -0,0 +1,249 @@
+use std::{path::Path, sync::OnceLock, time::{Duration, Instant}};
+
+use base64::Engine;
+use jsonwebtoken::{
+    DecodingKey, EncodingKey,
+    jwk::{AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse},
+};
+use ring::{digest, signature::{Ed25519KeyPair, KeyPair}};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{config::JwtConfig, services::database::account::AccountError};
+
+// DER prefix of an Ed25519 SubjectPublicKeyInfo; the raw 32-byte key follows it
+const ED25519_SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+// An unknown kid usually means the issuer rotated keys, but don't let bad tokens hammer the endpoint
+const JWKS_MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(30);
+// Requests wait on the refresh, so give up quickly and keep using the keys we have
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The Ed25519 key the `kt` service signs access tokens with.
+pub struct SigningKey {
+    pub key_id: String,
+    pub encoding_key: EncodingKey,
+}
+
+fn decode_pem(pem: &str, label: &str) -> Result<Vec<u8>, String> {
+    let begin = format!("-----BEGIN {label}-----");
+    let end = format!("-----END {label}-----");
+    let body = pem
+        .split_once(&begin)
+        .and_then(|(_, rest)| rest.split_once(&end))
+        .map(|(body, _)| body)
+        .ok_or_else(|| format!("Expected a {label} PEM block"))?;
+
+    let body: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .map_err(|e| format!("Invalid PEM encoding: {e}"))
+}
+
+/// Builds the public JWK for a raw Ed25519 key, identified by its RFC 7638 thumbprint.
+fn public_jwk(public_key: &[u8]) -> Jwk {
+    let x = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(public_key);
+    // Members in lexicographic order, as the thumbprint definition requires
+    let canonical = format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{x}"}}"#);
+    let key_id = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .encode(digest::digest(&digest::SHA256, canonical.as_bytes()));
+
+    Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(KeyAlgorithm::EdDSA),
+            key_id: Some(key_id),
+            ..Default::default()
+        },
+        algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x,
+        }),
+    }
+}
+
+fn read_signing_key(path: &Path) -> Result<(SigningKey, Jwk), String> {
+    let name = path.display();
+    let pem = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read signing key {name}: {e}"))?;
+    let der = decode_pem(&pem, "PRIVATE KEY")
+        .map_err(|e| format!("Failed to parse signing key {name}: {e}"))?;
+    let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(&der)
+        .map_err(|e| format!("Signing key {name} is not an Ed25519 PKCS#8 key: {e}"))?;
+
+    let jwk = public_jwk(key_pair.public_key().as_ref());
+    let key_id = jwk.common.key_id.clone().unwrap_or_default();
+
+    Ok((SigningKey { key_id, encoding_key: EncodingKey::from_ed_der(&der) }, jwk))
+}
+
+fn read_public_key(path: &Path) -> Result<Jwk, String> {
+    let name = path.display();
+    let pem = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read verification key {name}: {e}"))?;
+    let der = decode_pem(&pem, "PUBLIC KEY")
+        .map_err(|e| format!("Failed to parse verification key {name}: {e}"))?;
+
+    match der.strip_prefix(ED25519_SPKI_PREFIX.as_slice()) {
+        Some(public_key) if public_key.len() == 32 => Ok(public_jwk(public_key)),
+        _ => Err(format!("Verification key {name} is not an Ed25519 public key")),
+    }
+}
+
+/// Keys configured on this host: the signing key from `jwt.signing_key` and any
+/// retired or foreign public keys listed in `jwt.verification_keys`.
+struct LocalKeys {
+    signing_key: Option<SigningKey>,
+    jwks: JwkSet,
+}
+
+impl LocalKeys {
+    fn from_config(jwt: &JwtConfig) -> Result<Self, String> {
+        let mut keys = Vec::new();
+
+        let signing_key = match &jwt.signing_key {
+            Some(path) => {
+                let (signing_key, jwk) = read_signing_key(path)?;
+                keys.push(jwk);
+                Some(signing_key)
+            },
+            None => None,
+        };
+
+        for path in &jwt.verification_keys {
+            let jwk = read_public_key(path)?;
+            if !keys.iter().any(|key| key.common.key_id == jwk.common.key_id) {
+                keys.push(jwk);
+            }
+        }
+
+        Ok(Self { signing_key, jwks: JwkSet { keys } })
+    }
+}
+
+static LOCAL_KEYS: OnceLock<LocalKeys> = OnceLock::new();
+
+/// Loads the signing key and verification keys configured in `jwt`.
+pub fn init(jwt: &JwtConfig) -> Result<(), String> {
+    let keys = LocalKeys::from_config(jwt)?;
+    LOCAL_KEYS.set(keys).map_err(|_| "JWT keys are already loaded".to_string())
+}
+
+fn local_keys() -> Result<&'static LocalKeys, String> {
+    LOCAL_KEYS.get().ok_or_else(|| "JWT keys are not loaded".to_string())
+}
+
+/// The key new access tokens are signed with, loaded from `jwt.signing_key` by [`init`].
+pub fn signing_key() -> Result<&'static SigningKey, AccountError> {
+    local_keys()
+        .map_err(AccountError::TokenCreation)?
+        .signing_key
+        .as_ref()
+        .ok_or_else(|| AccountError::TokenCreation("jwt.signing_key is not configured".to_string()))
+}
+
+/// The public half of every key tokens may be verified with, as served from `/.well-known/jwks.json`.
+pub fn local_jwks() -> Result<&'static JwkSet, AccountError> {
+    local_keys()
+        .map(|keys| &keys.jwks)
+        .map_err(AccountError::TokenCreation)
+}
+
+struct CachedJwks {
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+static REMOTE_JWKS: RwLock<Option<CachedJwks>> = RwLock::const_new(None);
+
+/// Held while the JWKS is being fetched so only one request refreshes it at a time,
+/// recording when the last fetch started.
+static JWKS_REFRESH: Mutex<Option<Instant>> = Mutex::const_new(None);
+
+#[tracing::instrument]
+async fn fetch_jwks(url: &str) -> Result<JwkSet, AccountError> {
+    let client = reqwest::Client::builder()
+        .timeout(JWKS_FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| AccountError::Other(anyhow::anyhow!("Failed to build HTTP client: {e}")))?;
+
+    client.get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| AccountError::Other(anyhow::anyhow!("Failed to fetch JWKS from {url}: {e}")))?
+        .json::<JwkSet>()
+        .await
+        .map_err(|e| AccountError::Other(anyhow::anyhow!("Failed to parse JWKS from {url}: {e}")))
+}
+
+/// Looks `key_id` up in the cached JWKS. `None` means the cache needs a refresh first:
+/// it is empty, stale, or does not know the key and was not fetched just now.
+async fn cached_jwk(key_id: &str) -> Option<Option<Jwk>> {
+    let cache = REMOTE_JWKS.read().await;
+    let cached = cache.as_ref()?;
+    let jwk = cached.jwks.find(key_id).cloned();
+    let age = cached.fetched_at.elapsed();
+
+    (age < JWKS_REFRESH_INTERVAL && (jwk.is_some() || age < JWKS_MIN_REFETCH_INTERVAL)).then_some(jwk)
+}
+
+/// Looks `key_id` up in whatever JWKS is cached, however old.
+async fn stale_jwk(url: &str, key_id: &str, error: AccountError) -> Result<Option<Jwk>, AccountError> {
+    match REMOTE_JWKS.read().await.as_ref() {
+        Some(cached) => {
+            tracing::warn!(%url, "Using cached JWKS after failing to refresh it: {error}");
+            Ok(cached.jwks.find(key_id).cloned())
+        }
+        None => Err(error),
+    }
+}
+
+/// Looks `key_id` up in the JWKS at `url`, refreshing the cached copy when it is
+/// stale or does not know the key yet. The cache is never locked during the fetch, and
+/// when the fetch fails the keys fetched last time keep being used.
+async fn remote_jwk(url: &str, key_id: &str) -> Result<Option<Jwk>, AccountError> {
+    if let Some(jwk) = cached_jwk(key_id).await {
+        return Ok(jwk);
+    }
+
+    let mut last_fetch = JWKS_REFRESH.lock().await;
+    // Another request may have refreshed the cache while we waited for the lock
+    if let Some(jwk) = cached_jwk(key_id).await {
+        return Ok(jwk);
+    }
+    // Or tried to and failed; don't retry until the minimum interval has passed
+    if last_fetch.is_some_and(|started| started.elapsed() < JWKS_MIN_REFETCH_INTERVAL) {
+        let error = AccountError::Other(anyhow::anyhow!("JWKS from {url} is unavailable"));
+        return stale_jwk(url, key_id, error).await;
+    }
+
+    *last_fetch = Some(Instant::now());
+    match fetch_jwks(url).await {
+        Ok(jwks) => {
+            let jwk = jwks.find(key_id).cloned();
+            *REMOTE_JWKS.write().await = Some(CachedJwks { jwks, fetched_at: Instant::now() });
+            Ok(jwk)
+        }
+        Err(e) => stale_jwk(url, key_id, e).await,
+    }
+}
+
+/// Resolves the key a token's `kid` refers to. Hosts with local keys configured use those;
+/// otherwise keys come from the JWKS published at `jwt.jwks_url`.
+pub async fn verification_key(key_id: &str, jwt: &JwtConfig) -> Result<DecodingKey, AccountError> {
+    let local = local_jwks()?;
+
+    let jwk = if !local.keys.is_empty() {
+        local.find(key_id).cloned()
+    } else {
+        let url = jwt.jwks_url.as_deref().ok_or_else(|| {
+            AccountError::Other(anyhow::anyhow!("Neither jwt.verification_keys nor jwt.jwks_url is configured"))
+        })?;
+        remote_jwk(url, key_id).await?
+    };
+
+    let jwk = jwk.ok_or_else(|| AccountError::InvalidToken(format!("Unknown signing key {key_id}")))?;
+    DecodingKey::from_jwk(&jwk).map_err(|e| AccountError::InvalidToken(e.to_string()))
+}
@@ -1,10 +1,14 @@
+use std::time::Instant;
+
 use reqwest::Client;
 use serde::Deserialize;
 use futures::future::join_all;
 
 use crate::{
+    config::{GeneratorConfig, LlmConfig},
     enums::difficulty::Difficulty,
-    helpers::topic_conversion::skill_name_to_api_string,
+    helpers::topic_conversion::skill_slug_to_api_string,
+    monitoring::record_dependency_call,
     structs::{
         module_list::ModuleList,
         question_pair::QuestionPair
@@ -28,68 +32,84 @@ struct GenerateResponse {
     items: Vec<QuestionPair>,
 }
 
-static GENERATOR_URL: &str = "http://172.18.0.12:5000";
+#[tracing::instrument(skip_all)]
+pub async fn fetch_module_list(generator: &GeneratorConfig) -> Result<Vec<String>, GeneratorError> {
+    let started = Instant::now();
 
-pub async fn fetch_module_list() -> Result<Vec<String>, GeneratorError> {
+    let result = async {
+        let body = reqwest::get(format!("{}/modules", generator.url))
+            .await
+            .map_err(|e| GeneratorError::Connection(
+                format!("Failed to get generator response: {e}")
+            ))?;
 
-    let body = reqwest::get(format!("{GENERATOR_URL}/modules"))
-        .await
-        .map_err(|e| GeneratorError::Connection(
-            format!("Failed to get generator response: {e}")
-        ))?;
+        let module_list: ModuleList = body
+            .json()
+            .await
+            .map_err(|e| GeneratorError::Connection(
+                format!("Failed to parse generator response: {e}")
+            ))?;
 
-    let module_list: ModuleList = body
-        .json()
-        .await
-        .map_err(|e| GeneratorError::Connection(
-            format!("Failed to parse generator response: {e}")
-        ))?;
+        Ok(module_list.modules)
+    }.await;
 
-    Ok(module_list.modules)
+    record_dependency_call("generator", "modules", started, &result);
+    result
 }
 
+#[tracing::instrument(skip_all, fields(module = %module, difficulty = %difficulty))]
 async fn generate_single_question(
+    generator: &GeneratorConfig,
     module: String,
     difficulty: Difficulty,
 ) -> Result<QuestionPair, GeneratorError> {
 
     let client = Client::new();
 
-    let module = skill_name_to_api_string(&module).unwrap_or("error");
-
-    let response = client
-        .get(format!("{GENERATOR_URL}/generate"))
-        .query(&[
-            ("filter", module),
-            ("difficulty", &difficulty.to_string())
-        ])
-        .send()
-        .await
-        .map_err(|e| GeneratorError::Connection(
-            format!("Failed to contact generator: {e}")
-        ))?;
-
-    let body: GenerateResponse = response
-        .json()
-        .await
-        .map_err(|e| GeneratorError::Connection(
-            format!("Failed to parse generator response: {e}")
-        ))?;
-
-    body.items
-        .into_iter()
-        .next()
-        .ok_or_else(|| GeneratorError::Connection("No question generated".into()))
+    let module = skill_slug_to_api_string(&module).unwrap_or("error");
+
+    let started = Instant::now();
+
+    let result = async {
+        let response = client
+            .get(format!("{}/generate", generator.url))
+            .query(&[
+                ("filter", module),
+                ("difficulty", &difficulty.to_string())
+            ])
+            .send()
+            .await
+            .map_err(|e| GeneratorError::Connection(
+                format!("Failed to contact generator: {e}")
+            ))?;
+
+        let body: GenerateResponse = response
+            .json()
+            .await
+            .map_err(|e| GeneratorError::Connection(
+                format!("Failed to parse generator response: {e}")
+            ))?;
+
+        body.items
+            .into_iter()
+            .next()
+            .ok_or_else(|| GeneratorError::Connection("No question generated".into()))
+    }.await;
+
+    record_dependency_call("generator", "generate", started, &result);
+    result
 }
 
+#[tracing::instrument(skip(generator, difficulty))]
 pub async fn generate_questions(
+    generator: &GeneratorConfig,
     module: String,
     difficulty: Difficulty,
     amount: usize,
 ) -> Result<Vec<QuestionPair>, GeneratorError> {
 
     let futures = (0..amount).map(|_| {
-        generate_single_question(module.clone(), difficulty)
+        generate_single_question(generator, module.clone(), difficulty)
     });
 
     let results = join_all(futures).await;
@@ -103,19 +123,23 @@ pub async fn generate_questions(
     Ok(questions)
 }
 
+#[tracing::instrument(skip(generator, llm, difficulty))]
 pub async fn generate_word_questions(
+    generator: &GeneratorConfig,
+    llm: &LlmConfig,
     module: String,
     difficulty: Difficulty,
     amount: usize,
 ) -> Result<Vec<QuestionPair>, GeneratorError> {
 
-    let mut questions = generate_questions(module, difficulty, amount).await?;
+    let mut questions = generate_questions(generator, module, difficulty, amount).await?;
 
-    let api_key = std::env::var("OPENAI_API_KEY")
-        .map_err(|_| GeneratorError::GPT("Failed to fetch API key".into()))?;
+    let api_key = llm.api_key
+        .as_deref()
+        .ok_or_else(|| GeneratorError::GPT("OpenAI API key is not configured".into()))?;
 
     let client = Client::builder()
-        .timeout(std::time::Duration::from_secs(120))
+        .timeout(std::time::Duration::from_secs(llm.timeout))
         .build()
         .map_err(|e| GeneratorError::GPT(format!("Failed to build HTTP client: {e}")))?;
 
@@ -144,32 +168,7 @@ Questions:
         question_list
     );
 
-    let response = client
-        .post("https://api.openai.com/v1/responses")
-        .bearer_auth(api_key)
-        .json(&serde_json::json!({
-            "model": "gpt-5-nano",
-            "input": prompt
-        }))
-        .send()
-        .await
-        .map_err(|e| GeneratorError::GPT(format!("OpenAI request failed: {e}")))?;
-
-    let status = response.status();
-
-    if !status.is_success() {
-        let body = response.text().await.unwrap_or_default();
-
-        return Err(GeneratorError::GPT(format!(
-            "OpenAI returned {}: {}",
-            status, body
-        )));
-    }
-
-    let res: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| GeneratorError::GPT(format!("JSON parse error: {e}")))?;
+    let res = request_completion(&client, llm, api_key, prompt).await?;
 
     let output_text = res["output"]
         .as_array()
@@ -209,4 +208,46 @@ Questions:
     }
 
     Ok(questions)
+}
+
+#[tracing::instrument(skip_all, fields(model = %llm.model))]
+async fn request_completion(
+    client: &Client,
+    llm: &LlmConfig,
+    api_key: &str,
+    prompt: String,
+) -> Result<serde_json::Value, GeneratorError> {
+    let started = Instant::now();
+
+    let result = async {
+        let response = client
+            .post(&llm.url)
+            .bearer_auth(api_key)
+            .json(&serde_json::json!({
+                "model": llm.model,
+                "input": prompt
+            }))
+            .send()
+            .await
+            .map_err(|e| GeneratorError::GPT(format!("OpenAI request failed: {e}")))?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+
+            return Err(GeneratorError::GPT(format!(
+                "OpenAI returned {}: {}",
+                status, body
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| GeneratorError::GPT(format!("JSON parse error: {e}")))
+    }.await;
+
+    record_dependency_call("openai", "responses", started, &result);
+    result
 }
\ No newline at end of file
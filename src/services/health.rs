@@ -0,0 +1,91 @@
+use std::{collections::BTreeMap, time::{Duration, Instant}};
+
+use futures::future::join_all;
+
+use crate::{
+    config::{Config, GeneratorConfig},
+    enums::{dependency::Dependency, health_status::HealthStatus},
+    services::database::migrations::{latest_version, schema_version},
+    structs::health::{DependencyHealth, HealthReport},
+};
+
+/// Longest a single check may take before its dependency counts as down.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `Ok` carries an optional detail worth reporting, `Err` why the dependency is down. The report
+/// is public, so reasons are fixed strings and the underlying errors only go to the log.
+type CheckResult = Result<Option<String>, String>;
+
+async fn check_database() -> CheckResult {
+    let version = schema_version().await.map_err(|e| {
+        tracing::warn!("Database readiness check failed: {e}");
+        "Database unreachable".to_string()
+    })?;
+    let latest = latest_version();
+
+    if version < latest {
+        return Err(format!("Schema is at migration {version}, expected {latest}"));
+    }
+    Ok(Some(format!("Schema is at migration {version}")))
+}
+
+async fn check_generator(generator: &GeneratorConfig) -> CheckResult {
+    reqwest::get(format!("{}/modules", generator.url))
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| {
+            tracing::warn!("Generator readiness check failed: {e}");
+            "Generator unreachable".to_string()
+        })?;
+    Ok(None)
+}
+
+/// Word problems are the only thing the LLM is used for, so it is not needed while they are off.
+fn llm_status(config: &Config) -> (HealthStatus, Option<String>) {
+    if !config.features.word_problems {
+        return (HealthStatus::Disabled, Some("Word problems are disabled".to_string()));
+    }
+    match config.llm.api_key {
+        Some(_) => (HealthStatus::Up, None),
+        None => (HealthStatus::Down, Some("OpenAI API key is not configured".to_string())),
+    }
+}
+
+async fn run_check(check: impl Future<Output = CheckResult>) -> (HealthStatus, Option<String>) {
+    match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(Ok(detail)) => (HealthStatus::Up, detail),
+        Ok(Err(reason)) => (HealthStatus::Down, Some(reason)),
+        Err(_) => (HealthStatus::Down, Some(format!("No response within {}s", CHECK_TIMEOUT.as_secs()))),
+    }
+}
+
+#[tracing::instrument(skip(config))]
+async fn check(dependency: Dependency, config: &Config) -> DependencyHealth {
+    let started = Instant::now();
+
+    let (status, detail) = match dependency {
+        Dependency::Database => run_check(check_database()).await,
+        Dependency::Generator => run_check(check_generator(&config.generator)).await,
+        Dependency::Llm => llm_status(config),
+    };
+
+    DependencyHealth {
+        status,
+        latency_ms: started.elapsed().as_millis() as u64,
+        detail,
+    }
+}
+
+/// Checks every dependency concurrently.
+pub async fn readiness(dependencies: &[Dependency], config: &Config) -> HealthReport {
+    let results = join_all(dependencies.iter().map(|dependency| check(*dependency, config))).await;
+
+    let checks: BTreeMap<Dependency, DependencyHealth> = dependencies.iter().copied().zip(results).collect();
+    let status = if checks.values().any(|check| check.status == HealthStatus::Down) {
+        HealthStatus::Down
+    } else {
+        HealthStatus::Up
+    };
+
+    HealthReport { status, checks }
+}
@@ -1,2 +1,3 @@
 pub mod database;
-pub mod generator;
\ No newline at end of file
+pub mod generator;
+pub mod health;
\ No newline at end of file
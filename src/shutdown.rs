@@ -0,0 +1,73 @@
+use std::{
+    future::Future,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use tokio::{sync::watch, task::JoinHandle};
+
+/// Flips to `true` once a shutdown signal arrives.
+static SHUTDOWN: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::Sender::new(false));
+
+static WORKERS: Mutex<Vec<(&'static str, JoinHandle<()>)>> = Mutex::new(Vec::new());
+
+/// Longest a background worker gets to finish after being told to stop.
+const WORKER_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resolves once shutdown has begun. Background workers select on it to know when to stop.
+pub async fn requested() {
+    let mut receiver = SHUTDOWN.subscribe();
+    // The sender lives in a static, so it is never dropped
+    let _ = receiver.wait_for(|requested| *requested).await;
+}
+
+/// Resolves on the first SIGTERM or SIGINT, and tells background workers to stop.
+pub async fn signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for SIGINT: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = interrupt => tracing::info!("Received SIGINT, shutting down"),
+        () = terminate => tracing::info!("Received SIGTERM, shutting down"),
+    }
+
+    SHUTDOWN.send_replace(true);
+}
+
+/// Runs `worker` in the background until shutdown; it should return soon after [`requested`] resolves.
+pub fn spawn_worker(name: &'static str, worker: impl Future<Output = ()> + Send + 'static) {
+    let handle = tokio::spawn(worker);
+    WORKERS.lock().unwrap_or_else(|e| e.into_inner()).push((name, handle));
+}
+
+/// Waits for every background worker to finish, aborting any that outlive [`WORKER_STOP_TIMEOUT`].
+pub async fn stop_workers() {
+    SHUTDOWN.send_replace(true);
+
+    let workers = std::mem::take(&mut *WORKERS.lock().unwrap_or_else(|e| e.into_inner()));
+    for (name, mut handle) in workers {
+        if tokio::time::timeout(WORKER_STOP_TIMEOUT, &mut handle).await.is_err() {
+            tracing::warn!(worker = name, "Background worker did not stop in time; aborting it");
+            handle.abort();
+        }
+    }
+}
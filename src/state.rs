@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+
+use crate::{config::Config, helpers::password_policy::PasswordPolicy};
+
+/// Shared state handed to every router.
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<Config>,
+    pub password_policy: Arc<PasswordPolicy>,
+}
+
+impl AppState {
+    /// Builds the state once at startup, loading the password policy from `config`.
+    pub fn new(config: Arc<Config>) -> Result<Self, String> {
+        let password_policy = Arc::new(PasswordPolicy::from_config(&config.password)?);
+        Ok(Self { config, password_policy })
+    }
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<PasswordPolicy> {
+    fn from_ref(state: &AppState) -> Self {
+        state.password_policy.clone()
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::structs::skill::Skill;
+
+#[derive(Deserialize, ToSchema)]
+pub struct AssignmentCreate {
+    pub title: String,
+    /// Skill ids, slugs or display names
+    pub skills: Vec<String>,
+    pub target_mastery: f64,
+    pub question_count: i32,
+    /// RFC 3339 timestamp, e.g. `2026-03-01T09:00:00Z`
+    pub due_at: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Assignment {
+    pub assignment_id: i32,
+    pub class_id: i32,
+    pub title: String,
+    pub skills: Vec<Skill>,
+    pub target_mastery: f64,
+    pub question_count: i32,
+    pub due_at: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AssignmentSkillProgress {
+    #[serde(flatten)]
+    pub skill: Skill,
+    pub questions_answered: i32,
+    pub mastery: f64,
+    pub complete: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct StudentAssignment {
+    pub assignment: Assignment,
+    pub class_name: String,
+    pub skills: Vec<AssignmentSkillProgress>,
+    pub complete: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AssignmentStudentStatus {
+    pub user_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub skills: Vec<AssignmentSkillProgress>,
+    pub complete: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AssignmentStatus {
+    pub assignment: Assignment,
+    pub completed_students: usize,
+    pub students: Vec<AssignmentStudentStatus>,
+}
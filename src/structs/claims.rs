@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::enums::role::Role;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
@@ -8,4 +10,8 @@ pub struct Claims {
     pub iat: usize,
     pub iss: String,
     pub aud: String,
+    #[serde(default)]
+    pub sid: Option<i32>,
+    #[serde(default)]
+    pub role: Role,
 }
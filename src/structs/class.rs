@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::structs::{skill::Skill, skill_progression::SkillProgression};
+
+#[derive(Serialize, ToSchema)]
+pub struct Class {
+    pub class_id: i32,
+    pub class_name: String,
+    pub join_code: String,
+    pub teacher_id: i32,
+    pub created_at: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ClassCreate {
+    pub class_name: String
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ClassJoin {
+    pub join_code: String
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ClassStudent {
+    pub user_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub progression: Vec<SkillProgression>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SkillAverage {
+    #[serde(flatten)]
+    pub skill: Skill,
+    pub average_progression: f64,
+    pub student_count: i64,
+}
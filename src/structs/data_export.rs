@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    enums::role::Role,
+    structs::{
+        class::Class,
+        session::Session,
+        skill::Skill,
+        skill_progression::{SkillProgression, SkillProgressionWithDate},
+    },
+};
+
+#[derive(Serialize, ToSchema)]
+pub struct AccountDetails {
+    pub user_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub role: Role,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LoginAttempt {
+    pub ip_address: Option<String>,
+    pub succeeded: bool,
+    pub attempted_at: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AssignmentProgressRecord {
+    pub assignment_id: i32,
+    #[serde(flatten)]
+    pub skill: Skill,
+    pub questions_answered: i32,
+    pub mastery: f64,
+    pub updated_at: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DataExport {
+    pub exported_at: String,
+    pub account: AccountDetails,
+    pub progression: Vec<SkillProgression>,
+    pub historical_progression: Vec<SkillProgressionWithDate>,
+    pub sessions: Vec<Session>,
+    pub login_attempts: Vec<LoginAttempt>,
+    pub class_memberships: Vec<Class>,
+    pub classes_taught: Vec<Class>,
+    pub assignment_progress: Vec<AssignmentProgressRecord>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct AccountDeletion {
+    pub password: String
+}
@@ -0,0 +1,22 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::enums::{dependency::Dependency, health_status::HealthStatus};
+
+#[derive(Serialize, ToSchema)]
+pub struct DependencyHealth {
+    pub status: HealthStatus,
+    /// How long the check took
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Body of `/health/live` and `/health/ready`. The service is `down` when any dependency is.
+#[derive(Serialize, ToSchema)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: BTreeMap<Dependency, DependencyHealth>,
+}
@@ -0,0 +1,12 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct LockoutEvent {
+    pub lockout_id: i32,
+    pub scope: String,
+    pub lock_key: String,
+    pub failed_attempts: i64,
+    pub locked_until: String,
+    pub created_at: String,
+}
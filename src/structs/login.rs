@@ -0,0 +1,28 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::enums::role::Role;
+
+#[derive(Serialize, ToSchema)]
+pub struct UserProfile {
+    pub user_id: i32,
+    pub username: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: Role,
+}
+
+/// Body of a successful `/v2/accounts/login`.
+#[derive(Serialize, ToSchema)]
+pub struct LoginResponse {
+    pub access_token: String,
+    /// Opaque session token, exchanged for a new access token at `/accounts/validate`
+    pub refresh_token: String,
+    /// Always `Bearer`
+    pub token_type: &'static str,
+    /// Seconds until the access token expires
+    pub expires_in: u64,
+    /// RFC 3339 timestamp the access token expires at
+    pub expires_at: String,
+    pub user: UserProfile,
+}
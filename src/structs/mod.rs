@@ -7,4 +7,14 @@ pub mod account;
 pub mod sign_in;
 pub mod token_validation;
 pub mod claims;
-pub mod skill_progression;
\ No newline at end of file
+pub mod skill_progression;
+pub mod session;
+pub mod role_update;
+pub mod class;
+pub mod assignment;
+pub mod lockout_event;
+pub mod password;
+pub mod data_export;
+pub mod skill;
+pub mod login;
+pub mod health;
\ No newline at end of file
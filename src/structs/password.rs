@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordChange {
+    pub old_password: String,
+    pub new_password: String
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordReset {
+    pub reset_token: String,
+    pub new_password: String
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ResetToken {
+    pub reset_token: String,
+    pub expires_at: String,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::enums::role::Role;
+
+#[derive(Deserialize, ToSchema)]
+pub struct RoleUpdate {
+    pub role: Role
+}
@@ -0,0 +1,28 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{enums::role::Role, structs::login::UserProfile};
+
+#[derive(Serialize, ToSchema)]
+pub struct Session {
+    pub session_id: i32,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: String,
+    pub last_used_at: String,
+    pub current: bool,
+}
+
+pub struct SessionOwner {
+    pub session_id: i32,
+    pub user_id: i32,
+    pub role: Role,
+    pub audience: Option<String>,
+}
+
+/// A session opened by signing in, along with who it belongs to.
+pub struct NewSession {
+    pub refresh_token: [u8; 32],
+    pub owner: SessionOwner,
+    pub user: UserProfile,
+}
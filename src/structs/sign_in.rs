@@ -4,5 +4,8 @@ use utoipa::ToSchema;
 #[derive(Deserialize, ToSchema)]
 pub struct SignIn {
     pub username: String,
-    pub password: String
+    pub password: String,
+    /// Audience the issued access tokens are for; defaults to the desktop app
+    #[serde(default)]
+    pub audience: Option<String>
 }
\ No newline at end of file
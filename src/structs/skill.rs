@@ -0,0 +1,13 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// How a skill is identified in every response. `skill_id` and `skill_slug` never change,
+/// so clients should store one of those rather than the display name.
+#[derive(Serialize, ToSchema, Clone)]
+pub struct Skill {
+    pub skill_id: i32,
+    /// URL-safe identifier, e.g. `linear-equations-in-one-variable`
+    pub skill_slug: String,
+    /// Display name, which may be changed at any time
+    pub skill_name: String,
+}
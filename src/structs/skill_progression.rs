@@ -1,15 +1,19 @@
 use serde::Serialize;
 use utoipa::ToSchema;
 
+use crate::structs::skill::Skill;
+
 #[derive(Serialize, ToSchema)]
 pub struct SkillProgression {
-    pub skill_name: String,
+    #[serde(flatten)]
+    pub skill: Skill,
     pub progression: f64,
 }
 
 #[derive(Serialize, ToSchema)]
 pub struct SkillProgressionWithDate {
-    pub skill_name: String,
+    #[serde(flatten)]
+    pub skill: Skill,
     pub progression: f64,
     pub recorded_at: String,
-}
\ No newline at end of file
+}
@@ -0,0 +1,110 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::{
+    ClientConfig, RootCertStore, ServerConfig,
+    crypto::{CryptoProvider, ring},
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+};
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+use crate::shutdown;
+
+/// How often the certificate and key files are checked for changes.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certificates = CertificateDer::pem_file_iter(path)
+        .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read certificates from {}: {e}", path.display()))?;
+
+    if certificates.is_empty() {
+        return Err(format!("No certificates found in {}", path.display()));
+    }
+
+    Ok(certificates)
+}
+
+/// HTTPS settings serving the PEM certificate chain in `cert_file` with the private key in `key_file`.
+pub fn server_config(cert_file: &Path, key_file: &Path) -> Result<ServerConfig, String> {
+    let certificates = read_certificates(cert_file)?;
+    let key = PrivateKeyDer::from_pem_file(key_file)
+        .map_err(|e| format!("Failed to read private key from {}: {e}", key_file.display()))?;
+
+    let mut config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to set up TLS: {e}"))?
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+        .map_err(|e| format!("Failed to use certificate {} with key {}: {e}", cert_file.display(), key_file.display()))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(config)
+}
+
+/// Connector for Postgres over TLS. The server certificate and host name are always verified,
+/// against the CA certificates in `ca_file` when given and the public web roots otherwise.
+pub fn postgres_connector(ca_file: Option<&Path>) -> Result<MakeRustlsConnect, String> {
+    let mut roots = RootCertStore::empty();
+    match ca_file {
+        Some(path) => {
+            for certificate in read_certificates(path)? {
+                roots.add(certificate)
+                    .map_err(|e| format!("Invalid CA certificate in {}: {e}", path.display()))?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to set up TLS: {e}"))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    Ok(MakeRustlsConnect::new(config))
+}
+
+fn modified(paths: &[&Path]) -> Vec<Option<SystemTime>> {
+    paths.iter()
+        .map(|path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
+        .collect()
+}
+
+/// Starts a worker that swaps in the certificate and key whenever either file changes,
+/// so renewed certificates are picked up without a restart. New connections use the new
+/// certificate; a pair that fails to load is logged and the current one kept.
+pub fn reload_on_change(rustls: RustlsConfig, cert_file: PathBuf, key_file: PathBuf) {
+    shutdown::spawn_worker("tls_reload", async move {
+        let mut last_modified = modified(&[&cert_file, &key_file]);
+        let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+        loop {
+            tokio::select! {
+                () = shutdown::requested() => break,
+                _ = interval.tick() => {
+                    let current = modified(&[&cert_file, &key_file]);
+                    if current == last_modified {
+                        continue;
+                    }
+                    last_modified = current;
+
+                    match server_config(&cert_file, &key_file) {
+                        Ok(config) => {
+                            rustls.reload_from_config(Arc::new(config));
+                            tracing::info!(cert_file = %cert_file.display(), "Reloaded TLS certificate");
+                        }
+                        Err(e) => tracing::error!("Keeping the current TLS certificate: {e}"),
+                    }
+                }
+            }
+        }
+    });
+}